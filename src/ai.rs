@@ -0,0 +1,535 @@
+use super::pieces::{Color,Kind};
+use super::board::Board;
+use super::moves::Move;
+use std::sync::atomic::{AtomicBool,AtomicU64,Ordering};
+use std::time::{Duration,Instant};
+
+mod eval;
+mod ordering;
+mod tt;
+
+use ordering::{MoveOrdering,MovePicker};
+pub use tt::{Bound,Entry,TranspositionTable};
+
+// scores within MAX_PLY of MATE are forced mates, the rest are centipawns
+pub const MATE: i32 = 100_000;
+const MAX_PLY: u32 = 64;
+// half width of the first aspiration window, in centipawns
+const ASPIRATION_WINDOW: i32 = 50;
+// room left for positional gains when delta pruning captures in quiescence
+const DELTA_MARGIN: i32 = 200;
+// whether quiescence also tries quiet moves that give check on its first ply
+const CHECKS_IN_QUIESCENCE: bool = true;
+
+/// A move as plain coordinates, the form moves took before [`Move`]. Convert
+/// with `Ply::from(mv)` and `Move::from_ply`.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct Ply {
+    pub fromx: i32,
+    pub fromy: i32,
+    pub tox: i32,
+    pub toy: i32,
+    pub promotion: Option<Kind>,
+}
+
+/// When to stop searching. With nothing set the search runs until it is told to stop.
+#[derive(Clone,Debug,Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub nodes: Option<u64>,
+    pub infinite: bool,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub move_overhead: Duration,
+    /// How many threads search together, one when left at zero.
+    pub threads: usize,
+}
+impl SearchLimits {
+    pub fn depth(depth: u32) -> Self {
+        Self { depth: Some(depth), ..Default::default() }
+    }
+    /// A fixed time per move, as the GUI uses.
+    pub fn movetime(movetime: Duration) -> Self {
+        Self { movetime: Some(movetime), ..Default::default() }
+    }
+    // (soft,hard): no new iteration is started after the soft limit, and the
+    // search is cut off at the hard one
+    fn time_limits(&self, color: Color) -> Option<(Duration,Duration)> {
+        if self.infinite {
+            return None;
+        }
+        if let Some(movetime) = self.movetime {
+            let budget = movetime.saturating_sub(self.move_overhead);
+            return Some((budget,budget));
+        }
+        let (time,inc) = match color {
+            Color::WHITE => (self.wtime?, self.winc.unwrap_or_default()),
+            Color::BLACK => (self.btime?, self.binc.unwrap_or_default()),
+        };
+        let time = time.saturating_sub(self.move_overhead);
+        let moves_left = self.movestogo.unwrap_or(30).max(1);
+        // never risk more than a third of what is left on the clock
+        let hard = ((time / moves_left + inc) * 4).min(time / 3);
+        let soft = (time / moves_left + inc * 3 / 4).min(hard);
+        Some((soft,hard))
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Mate in this many moves, negative when we are the one getting mated.
+    Mate(i32),
+}
+impl Score {
+    pub fn from_value(value: i32) -> Self {
+        if value.abs() >= MATE - MAX_PLY as i32 {
+            let plies = MATE - value.abs();
+            let moves = (plies + 1) / 2;
+            Score::Mate(if value > 0 { moves } else { -moves })
+        }
+        else {
+            Score::Centipawns(value)
+        }
+    }
+}
+
+/// Progress report sent after every completed depth.
+#[derive(Clone,Debug)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub time: Duration,
+    /// Permille of the transposition table in use.
+    pub hashfull: u32,
+    pub pv: Vec<Move>,
+}
+impl SearchInfo {
+    pub fn nps(&self) -> u64 {
+        let millis = self.time.as_millis().max(1) as u64;
+        self.nodes * 1000 / millis
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: Score,
+    pub pv: Vec<Move>,
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+struct SearchContext<'a> {
+    tt: &'a TranspositionTable,
+    ordering: MoveOrdering,
+    // the moves leading from the root to the current node
+    line: Vec<Move>,
+    stop: &'a AtomicBool,
+    start: Instant,
+    deadline: Option<Instant>,
+    // no new depth is started once this much of the time is gone
+    soft_limit: Option<Duration>,
+    node_limit: Option<u64>,
+    nodes: u64,
+    // the nodes of every thread, added in batches
+    total_nodes: &'a AtomicU64,
+    aborted: bool,
+}
+impl<'a> SearchContext<'a> {
+    fn new(tt: &'a TranspositionTable, stop: &'a AtomicBool, total_nodes: &'a AtomicU64, start: Instant) -> Self {
+        Self {
+            tt,
+            ordering: MoveOrdering::new(),
+            line: Vec::<Move>::new(),
+            stop,
+            start,
+            deadline: None,
+            soft_limit: None,
+            node_limit: None,
+            nodes: 0,
+            total_nodes,
+            aborted: false,
+        }
+    }
+    fn should_stop(&mut self) -> bool {
+        if !self.aborted {
+            // checking the clock on every node is wasteful, and so is sharing the count
+            let checkpoint = self.nodes.is_multiple_of(1024);
+            if checkpoint {
+                self.total_nodes.fetch_add(1024, Ordering::Relaxed);
+            }
+            let out_of_time = checkpoint
+                && self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || out_of_time
+                || self.node_limit.is_some_and(|limit| self.total_nodes() >= limit);
+        }
+        self.aborted
+    }
+    fn total_nodes(&self) -> u64 {
+        self.total_nodes.load(Ordering::Relaxed) + self.nodes % 1024
+    }
+}
+
+/// Searches deeper and deeper until a limit is hit or `stop` is set, calling
+/// `on_info` after each finished depth. A depth that is cut off part way is thrown
+/// away and the best move of the last finished depth is returned.
+///
+/// With more than one thread this is a Lazy SMP search: helper threads run the
+/// same search alongside, half of them a depth ahead, and share what they find
+/// through the transposition table. The helpers stop when the main thread does,
+/// and a helper's result is taken over the main thread's if it got deeper.
+pub fn search(board: &Board, limits: &SearchLimits, tt: &mut TranspositionTable, stop: &AtomicBool, on_info: &mut dyn FnMut(&SearchInfo)) -> SearchResult {
+    let start = Instant::now();
+    let time_limits = limits.time_limits(board.player_turn());
+    tt.new_search();
+    let tt = &*tt;
+    let moves = board.get_moves_2(board.player_turn());
+    let result = SearchResult {
+        best_move: moves.first().copied(),
+        score: Score::Centipawns(0),
+        pv: Vec::<Move>::new(),
+        depth: 0,
+        nodes: 0,
+    };
+    if board.result.is_some() || moves.is_empty() {
+        return result;
+    }
+    let max_depth = limits.depth.unwrap_or(MAX_PLY).min(MAX_PLY);
+    let total_nodes = AtomicU64::new(0);
+    // set once the main thread is done, which is what stops the helpers
+    let finished = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        let helpers: Vec<_> = (1..limits.threads.max(1)).map(|id| {
+            let (finished,total_nodes,result) = (&finished, &total_nodes, result.clone());
+            scope.spawn(move || {
+                let mut context = SearchContext::new(tt, finished, total_nodes, start);
+                iterate(board, 1 + id as u32 % 2, max_depth, result, &mut context, &mut |_| ())
+            })
+        }).collect();
+        let mut context = SearchContext::new(tt, stop, &total_nodes, start);
+        context.deadline = time_limits.map(|(_,hard)| start + hard);
+        context.soft_limit = time_limits.map(|(soft,_)| soft);
+        context.node_limit = limits.nodes;
+        let mut result = iterate(board, 1, max_depth, result, &mut context, on_info);
+        finished.store(true, Ordering::Relaxed);
+        let mut nodes = result.nodes;
+        for helper in helpers {
+            let helper = helper.join().expect("~search thread panicked");
+            nodes += helper.nodes;
+            if helper.depth > result.depth {
+                result = helper;
+            }
+        }
+        result.nodes = nodes;
+        result
+    })
+}
+
+// One thread's iterative deepening, from `first_depth` up. Starts from `result`
+// and returns it unchanged when not even the first depth gets finished.
+fn iterate(board: &Board, first_depth: u32, max_depth: u32, mut result: SearchResult, context: &mut SearchContext, on_info: &mut dyn FnMut(&SearchInfo)) -> SearchResult {
+    // the search plays its moves on this one copy and takes them back again
+    let mut root = board.clone();
+    let mut value: i32 = 0;
+    for depth in first_depth..=max_depth {
+        // expect the score to stay close to the last one, widening the window when it doesn't
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut alpha,mut beta) = if depth >= 4 && value.abs() < MATE - MAX_PLY as i32 {
+            (value - delta, value + delta)
+        }
+        else {
+            (-MATE, MATE)
+        };
+        let mut pv = Vec::<Move>::new();
+        loop {
+            pv.clear();
+            // searching last iteration's best line first gives the most cutoffs
+            value = negamax(&mut root, depth, 0, alpha, beta, &result.pv, context, &mut pv);
+            if context.aborted {
+                break;
+            }
+            if value <= alpha {
+                alpha = (value - delta).max(-MATE);
+            }
+            else if value >= beta {
+                beta = (value + delta).min(MATE);
+            }
+            else {
+                break;
+            }
+            delta *= 2;
+        }
+        if context.aborted {
+            break;
+        }
+        result = SearchResult {
+            best_move: pv.first().copied(),
+            score: Score::from_value(value),
+            pv,
+            depth,
+            nodes: context.nodes,
+        };
+        on_info(&SearchInfo {
+            depth,
+            score: result.score,
+            nodes: context.total_nodes(),
+            time: context.start.elapsed(),
+            hashfull: context.tt.hashfull(),
+            pv: result.pv.clone(),
+        });
+        // a forced mate won't get any better by looking further
+        if value.abs() >= MATE - depth as i32 {
+            break;
+        }
+        // the next depth takes longer than all the previous ones put together
+        if context.soft_limit.is_some_and(|soft| context.start.elapsed() * 2 > soft) {
+            break;
+        }
+    }
+    result.nodes = context.nodes;
+    result
+}
+
+/// Searches within `limits` and plays the best move found on `board`.
+pub fn make_move(board: &mut Board, limits: &SearchLimits, tt: &mut TranspositionTable) -> Option<Move> {
+    if board.result.is_some() {
+        return None;
+    }
+    let stop = AtomicBool::new(false);
+    let best_move = search(board, limits, tt, &stop, &mut |_| ()).best_move?;
+    board.selected_tile = Some(best_move.from_square());
+    board.perform_move_2(best_move);
+    Some(best_move)
+}
+
+// Principal variation search: the first move gets the full window and the rest
+// only have to prove they are no better, getting a full re-search when they are.
+// Scores are from the side to move's point of view.
+#[allow(clippy::too_many_arguments)]
+fn negamax(board: &mut Board, depth: u32, ply: u32, mut alpha: i32, beta: i32, prev_pv: &[Move], context: &mut SearchContext, pv: &mut Vec<Move>) -> i32 {
+    if depth == 0 {
+        return quiescence(board, ply, alpha, beta, CHECKS_IN_QUIESCENCE, context);
+    }
+    context.nodes += 1;
+    if context.should_stop() {
+        return 0;
+    }
+    // moves are made without working out the result, so draws are looked for here
+    if is_draw(board, ply) {
+        return 0;
+    }
+    let entry = context.tt.probe(board.hash());
+    // only null window nodes take cutoffs from the table, so principal variations stay whole
+    if let Some(entry) = entry.filter(|entry| entry.depth >= depth && beta - alpha == 1) {
+        let score = entry.score(ply);
+        let cutoff = match entry.bound {
+            Bound::Exact => true,
+            Bound::Lower => score >= beta,
+            Bound::Upper => score <= alpha,
+        };
+        if cutoff {
+            return score;
+        }
+    }
+    let (pv_move,child_prev_pv) = match prev_pv.split_first() {
+        Some((pv_move,rest)) => (Some(*pv_move), rest),
+        None => (None, &[][..]),
+    };
+    let hash_move = pv_move.or(entry.and_then(|entry| entry.best_move));
+    let prev_move = context.line.last().copied();
+    let mut picker = MovePicker::new(hash_move, &context.ordering, ply, prev_move);
+    let alpha_orig = alpha;
+    let mut best = -MATE;
+    let mut best_move = None;
+    let mut quiets_tried = Vec::<Move>::new();
+    let mut child_pv = Vec::<Move>::new();
+    let mut i = 0;
+    while let Some(next) = picker.next(board, &context.ordering) {
+        board.make_move(next);
+        // only the first move continues along the previous principal variation
+        let prev = if i == 0 && pv_move == Some(next) { child_prev_pv } else { &[] };
+        child_pv.clear();
+        context.line.push(next);
+        let mut value;
+        if i == 0 {
+            value = -negamax(board, depth-1, ply+1, -beta, -alpha, prev, context, &mut child_pv);
+        }
+        else {
+            value = -negamax(board, depth-1, ply+1, -alpha-1, -alpha, prev, context, &mut child_pv);
+            if value > alpha && value < beta {
+                child_pv.clear();
+                value = -negamax(board, depth-1, ply+1, -beta, -alpha, prev, context, &mut child_pv);
+            }
+        }
+        board.unmake_move();
+        context.line.pop();
+        if context.aborted {
+            return 0;
+        }
+        let quiet = !next.is_capture() && !next.is_promotion();
+        if value > best {
+            best = value;
+            best_move = Some(next);
+            if value > alpha {
+                alpha = value;
+                pv.clear();
+                pv.push(next);
+                pv.extend_from_slice(&child_pv);
+            }
+            if alpha >= beta {
+                if quiet {
+                    context.ordering.quiet_cutoff(board.player_turn(), ply, prev_move, next, &quiets_tried, depth);
+                }
+                break;
+            }
+        }
+        if quiet {
+            quiets_tried.push(next);
+        }
+        i += 1;
+    }
+    if best_move.is_none() {
+        return no_moves_score(board, ply);
+    }
+    let bound = if best <= alpha_orig {
+        Bound::Upper
+    }
+    else if best >= beta {
+        Bound::Lower
+    }
+    else {
+        Bound::Exact
+    };
+    context.tt.store(board.hash(), best_move, best, depth, bound, ply);
+    best
+}
+
+// Keeps searching captures and promotions past the depth limit until the position
+// is quiet, so a piece is never counted as won when it is about to be taken back.
+// The side to move may "stand pat" on the static score instead of capturing,
+// except when in check, where every evasion is searched.
+fn quiescence(board: &mut Board, ply: u32, mut alpha: i32, beta: i32, checks: bool, context: &mut SearchContext) -> i32 {
+    context.nodes += 1;
+    if context.should_stop() {
+        return 0;
+    }
+    if is_draw(board, ply) {
+        return 0;
+    }
+    if ply >= 2 * MAX_PLY {
+        return eval::evaluate(board);
+    }
+    // the legal moves come first so mate and stalemate aren't stood pat on
+    let legal_moves = board.legal_moves();
+    if legal_moves.is_empty() {
+        return no_moves_score(board, ply);
+    }
+    let in_check = board.in_check(board.player_turn());
+    let stand_pat = eval::evaluate(board);
+    if !in_check {
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+    }
+    let mut moves: Vec<(Move,i32)> = legal_moves.into_iter()
+        .filter_map(|next| {
+            if in_check {
+                return Some((next, 0));
+            }
+            let tactical = next.is_capture() || next.is_promotion();
+            if !tactical {
+                // quiet checks are only tried on the first ply, they are too many after that
+                let gives_check = checks && board.gives_check(next);
+                return gives_check.then_some((next, 0));
+            }
+            // delta pruning: even winning this piece for free can't bring us back to alpha
+            let captured = board.captured_piece(next).map_or(0, |piece| piece_value(piece.kind));
+            let promotion = next.promotion().map_or(0, |kind| piece_value(kind) - piece_value(Kind::PAWN));
+            if stand_pat + captured + promotion + DELTA_MARGIN < alpha {
+                return None;
+            }
+            // captures that lose material in the exchange aren't worth looking at
+            let see = board.see(next, piece_value);
+            (see >= 0).then_some((next, see))
+        })
+        .collect();
+    moves.sort_by_key(|(_,see)| -see);
+    let mut best = if in_check { -MATE } else { stand_pat };
+    for (next,_) in moves {
+        board.make_move(next);
+        let value = -quiescence(board, ply+1, -beta, -alpha, false, context);
+        board.unmake_move();
+        if context.aborted {
+            return 0;
+        }
+        if value > best {
+            best = value;
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+    }
+    best
+}
+
+// Below the root a repeated position or a fifty-move claim counts as a draw too: the
+// side that is worse off can take it, and repeating once means it can be repeated again.
+fn is_draw(board: &Board, ply: u32) -> bool {
+    board.automatic_draw().is_some() || ply > 0 && (board.repetitions() >= 2 || board.halfmove_clock() >= 100)
+}
+
+// checkmate or stalemate for the side to move, quicker mates score higher
+fn no_moves_score(board: &Board, ply: u32) -> i32 {
+    if board.in_check(board.player_turn()) {
+        -(MATE - ply as i32)
+    }
+    else {
+        0
+    }
+}
+
+// rough centipawn values for exchanges and move ordering
+fn piece_value(kind: Kind) -> i32 {
+    match kind {
+        Kind::PAWN => 100,
+        Kind::KNIGHT => 300,
+        Kind::BISHOP => 300,
+        Kind::ROOK => 500,
+        Kind::QUEEN => 900,
+        Kind::KING => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::board::START_FEN;
+    use super::super::notation;
+
+    #[test]
+    fn repetitions_and_fifty_moves_are_draws_below_the_root() {
+        let mut board = Board::from_fen(START_FEN).unwrap();
+        for san in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            board.perform_move_2(notation::parse_san(&board, san).unwrap());
+        }
+        assert_eq!(board.repetitions(), 2);
+        assert!(is_draw(&board, 1));
+        // the root itself still gets searched for a move
+        assert!(!is_draw(&board, 0));
+        let mut tt = TranspositionTable::new(1);
+        let result = search(&board, &SearchLimits::depth(3), &mut tt, &AtomicBool::new(false), &mut |_| ());
+        assert!(result.best_move.is_some());
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80").unwrap();
+        assert!(is_draw(&board, 1) && !is_draw(&board, 0));
+    }
+}
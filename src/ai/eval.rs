@@ -0,0 +1,384 @@
+// Tapered evaluation: every term has a middlegame and an endgame value, blended
+// by how much material is left. Tables and material values are the PeSTO ones.
+use super::super::board::Board;
+use super::super::pieces::{ChessPiece,Color,Kind};
+use super::super::square::Square;
+
+const MG_PAWN: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,  0,   0,
+     98, 134,  61,  95,  68, 126, 34, -11,
+     -6,   7,  26,  31,  65,  56, 25, -20,
+    -14,  13,   6,  21,  23,  12, 17, -23,
+    -27,  -2,  -5,  12,  17,   6, 10, -25,
+    -26,  -4,  -4, -10,   3,   3, 33, -12,
+    -35,  -1, -20, -23, -15,  24, 38, -22,
+      0,   0,   0,   0,   0,   0,  0,   0,
+];
+const EG_PAWN: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+    178, 173, 158, 134, 147, 132, 165, 187,
+     94, 100,  85,  67,  56,  53,  82,  84,
+     32,  24,  13,   5,  -2,   4,  17,  17,
+     13,   9,  -3,  -7,  -7,  -8,   3,  -1,
+      4,   7,  -6,   1,   0,  -5,  -1,  -8,
+     13,   8,   8,  10,  13,   0,   2,  -7,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+const MG_KNIGHT: [i32; 64] = [
+    -167, -89, -34, -49,  61, -97, -15, -107,
+     -73, -41,  72,  36,  23,  62,   7,  -17,
+     -47,  60,  37,  65,  84, 129,  73,   44,
+      -9,  17,  19,  53,  37,  69,  18,   22,
+     -13,   4,  16,  13,  28,  19,  21,   -8,
+     -23,  -9,  12,  10,  19,  17,  25,  -16,
+     -29, -53, -12,  -3,  -1,  18, -14,  -19,
+    -105, -21, -58, -33, -17, -28, -19,  -23,
+];
+const EG_KNIGHT: [i32; 64] = [
+    -58, -38, -13, -28, -31, -27, -63, -99,
+    -25,  -8, -25,  -2,  -9, -25, -24, -52,
+    -24, -20,  10,   9,  -1,  -9, -19, -41,
+    -17,   3,  22,  22,  22,  11,   8, -18,
+    -18,  -6,  16,  25,  16,  17,   4, -18,
+    -23,  -3,  -1,  15,  10,  -3, -20, -22,
+    -42, -20, -10,  -5,  -2, -20, -23, -44,
+    -29, -51, -23, -15, -22, -18, -50, -64,
+];
+const MG_BISHOP: [i32; 64] = [
+    -29,   4, -82, -37, -25, -42,   7,  -8,
+    -26,  16, -18, -13,  30,  59,  18, -47,
+    -16,  37,  43,  40,  35,  50,  37,  -2,
+     -4,   5,  19,  50,  37,  37,   7,  -2,
+     -6,  13,  13,  26,  34,  12,  10,   4,
+      0,  15,  15,  15,  14,  27,  18,  10,
+      4,  15,  16,   0,   7,  21,  33,   1,
+    -33,  -3, -14, -21, -13, -12, -39, -21,
+];
+const EG_BISHOP: [i32; 64] = [
+    -14, -21, -11,  -8, -7,  -9, -17, -24,
+     -8,  -4,   7, -12, -3, -13,  -4, -14,
+      2,  -8,   0,  -1, -2,   6,   0,   4,
+     -3,   9,  12,   9, 14,  10,   3,   2,
+     -6,   3,  13,  19,  7,  10,  -3,  -9,
+    -12,  -3,   8,  10, 13,   3,  -7, -15,
+    -14, -18,  -7,  -1,  4,  -9, -15, -27,
+    -23,  -9, -23,  -5, -9, -16,  -5, -17,
+];
+const MG_ROOK: [i32; 64] = [
+     32,  42,  32,  51, 63,  9,  31,  43,
+     27,  32,  58,  62, 80, 67,  26,  44,
+     -5,  19,  26,  36, 17, 45,  61,  16,
+    -24, -11,   7,  26, 24, 35,  -8, -20,
+    -36, -26, -12,  -1,  9, -7,   6, -23,
+    -45, -25, -16, -17,  3,  0,  -5, -33,
+    -44, -16, -20,  -9, -1, 11,  -6, -71,
+    -19, -13,   1,  17, 16,  7, -37, -26,
+];
+const EG_ROOK: [i32; 64] = [
+    13, 10, 18, 15, 12,  12,   8,   5,
+    11, 13, 13, 11, -3,   3,   8,   3,
+     7,  7,  7,  5,  4,  -3,  -5,  -3,
+     4,  3, 13,  1,  2,   1,  -1,   2,
+     3,  5,  8,  4, -5,  -6,  -8, -11,
+    -4,  0, -5, -1, -7, -12,  -8, -16,
+    -6, -6,  0,  2, -9,  -9, -11,  -3,
+    -9,  2,  3, -1, -5, -13,   4, -20,
+];
+const MG_QUEEN: [i32; 64] = [
+    -28,   0,  29,  12,  59,  44,  43,  45,
+    -24, -39,  -5,   1, -16,  57,  28,  54,
+    -13, -17,   7,   8,  29,  56,  47,  57,
+    -27, -27, -16, -16,  -1,  17,  -2,   1,
+     -9, -26,  -9, -10,  -2,  -4,   3,  -3,
+    -14,   2, -11,  -2,  -5,   2,  14,   5,
+    -35,  -8,  11,   2,   8,  15,  -3,   1,
+     -1, -18,  -9,  10, -15, -25, -31, -50,
+];
+const EG_QUEEN: [i32; 64] = [
+     -9,  22,  22,  27,  27,  19,  10,  20,
+    -17,  20,  32,  41,  58,  25,  30,   0,
+    -20,   6,   9,  49,  47,  35,  19,   9,
+      3,  22,  24,  45,  57,  40,  57,  36,
+    -18,  28,  19,  47,  31,  34,  39,  23,
+    -16, -27,  15,   6,   9,  17,  10,   5,
+    -22, -23, -30, -16, -16, -23, -36, -32,
+    -33, -28, -22, -43,  -5, -32, -20, -41,
+];
+const MG_KING: [i32; 64] = [
+    -65,  23,  16, -15, -56, -34,   2,  13,
+     29,  -1, -20,  -7,  -8,  -4, -38, -29,
+     -9,  24,   2, -16, -20,   6,  22, -22,
+    -17, -20, -12, -27, -30, -25, -14, -36,
+    -49,  -1, -27, -39, -46, -44, -33, -51,
+    -14, -14, -22, -46, -44, -30, -15, -27,
+      1,   7,  -8, -64, -43, -16,   9,   8,
+    -15,  36,  12, -54,   8, -28,  24,  14,
+];
+const EG_KING: [i32; 64] = [
+    -74, -35, -18, -18, -11,  15,   4, -17,
+    -12,  17,  14,  17,  17,  38,  23,  11,
+     10,  17,  23,  15,  20,  45,  44,  13,
+     -8,  22,  24,  27,  26,  33,  26,   3,
+    -18,  -4,  21,  24,  27,  23,   9, -11,
+    -19,  -3,  11,  21,  23,  16,   7,  -9,
+    -27, -11,   4,  13,  14,   4,  -5, -17,
+    -53, -34, -21, -11, -28, -14, -24, -43,
+];
+
+// (middlegame, endgame) in centipawns
+const BISHOP_PAIR: (i32,i32) = (30, 50);
+const DOUBLED_PAWN: (i32,i32) = (-10, -20);
+const ISOLATED_PAWN: (i32,i32) = (-10, -15);
+// by how far the pawn has advanced
+const PASSED_PAWN: [(i32,i32); 8] = [(0,0), (5,10), (10,15), (15,30), (25,55), (40,90), (60,140), (0,0)];
+const ROOK_OPEN_FILE: (i32,i32) = (25, 10);
+const ROOK_HALF_OPEN_FILE: (i32,i32) = (10, 5);
+const PAWN_SHIELD: i32 = 10;
+const OPEN_FILE_NEAR_KING: i32 = -15;
+// middlegame material weights: the game starts at 24 and ends at 0
+const KNIGHT_PHASE: i32 = 1;
+const BISHOP_PHASE: i32 = 1;
+const ROOK_PHASE: i32 = 2;
+const QUEEN_PHASE: i32 = 4;
+const MAX_PHASE: i32 = 24;
+
+fn material(kind: Kind) -> (i32,i32) {
+    match kind {
+        Kind::PAWN => (82, 94),
+        Kind::KNIGHT => (337, 281),
+        Kind::BISHOP => (365, 297),
+        Kind::ROOK => (477, 512),
+        Kind::QUEEN => (1025, 936),
+        Kind::KING => (0, 0),
+    }
+}
+
+fn tables(kind: Kind) -> (&'static [i32; 64], &'static [i32; 64]) {
+    match kind {
+        Kind::PAWN => (&MG_PAWN, &EG_PAWN),
+        Kind::KNIGHT => (&MG_KNIGHT, &EG_KNIGHT),
+        Kind::BISHOP => (&MG_BISHOP, &EG_BISHOP),
+        Kind::ROOK => (&MG_ROOK, &EG_ROOK),
+        Kind::QUEEN => (&MG_QUEEN, &EG_QUEEN),
+        Kind::KING => (&MG_KING, &EG_KING),
+    }
+}
+
+// (per square over the baseline, baseline) for the pieces whose mobility counts
+fn mobility_weight(kind: Kind) -> Option<((i32,i32), i32)> {
+    match kind {
+        Kind::KNIGHT => Some(((4,4), 4)),
+        Kind::BISHOP => Some(((5,5), 7)),
+        Kind::ROOK => Some(((2,4), 7)),
+        Kind::QUEEN => Some(((1,2), 14)),
+        _ => None,
+    }
+}
+
+// how much each piece's attacks next to the enemy king worry it
+fn king_attack_weight(kind: Kind) -> i32 {
+    match kind {
+        Kind::KNIGHT | Kind::BISHOP => 2,
+        Kind::ROOK => 3,
+        Kind::QUEEN => 5,
+        _ => 0,
+    }
+}
+
+// the tables are written from white's side with a8 first
+fn table_index(color: Color, x: i32, y: i32) -> usize {
+    match color {
+        Color::WHITE => ((7 - y) * 8 + x) as usize,
+        Color::BLACK => (y * 8 + x) as usize,
+    }
+}
+
+// how many ranks a piece has advanced from its own side
+fn relative_rank(color: Color, y: i32) -> i32 {
+    match color {
+        Color::WHITE => y,
+        Color::BLACK => 7 - y,
+    }
+}
+
+#[derive(Default,Clone,Copy)]
+struct Terms {
+    mg: i32,
+    eg: i32,
+}
+impl Terms {
+    fn add(&mut self, (mg,eg): (i32,i32)) {
+        self.mg += mg;
+        self.eg += eg;
+    }
+}
+
+/// Static evaluation in centipawns from the side to move's point of view.
+pub fn evaluate(board: &Board) -> i32 {
+    let pieces = board.get_pieces();
+    let mut terms = [Terms::default(); 2];
+    let mut phase = 0;
+    let mut bishops = [0; 2];
+    let mut pawn_files = [[0; 8]; 2];
+    let mut kings = [(0,0); 2];
+    for (piece,square) in &pieces {
+        let (x,y) = square.coords();
+        let s = piece.color.index();
+        let (mg_table,eg_table) = tables(piece.kind);
+        let index = table_index(piece.color, x, y);
+        let (mg,eg) = material(piece.kind);
+        terms[s].add((mg + mg_table[index], eg + eg_table[index]));
+        phase += match piece.kind {
+            Kind::KNIGHT => KNIGHT_PHASE,
+            Kind::BISHOP => BISHOP_PHASE,
+            Kind::ROOK => ROOK_PHASE,
+            Kind::QUEEN => QUEEN_PHASE,
+            _ => 0,
+        };
+        match piece.kind {
+            Kind::BISHOP => bishops[s] += 1,
+            Kind::PAWN => pawn_files[s][x as usize] += 1,
+            Kind::KING => kings[s] = (x,y),
+            _ => (),
+        }
+    }
+    // the enemy king's tile and every tile next to it
+    let king_zones = kings.map(|(kx,ky)| {
+        let square = Square::from_coords(kx,ky).expect("~king off the board");
+        board.attacks(ChessPiece::new(Color::WHITE, Kind::KING), square) | square.bitboard()
+    });
+    let mut king_attack = [0; 2];
+    for (piece,square) in &pieces {
+        let (x,y) = square.coords();
+        let s = piece.color.index();
+        let them = 1 - s;
+        match piece.kind {
+            Kind::PAWN => terms[s].add(pawn_terms(board, *piece, x, y, &pawn_files)),
+            Kind::ROOK => {
+                let file = x as usize;
+                if pawn_files[s][file] == 0 {
+                    terms[s].add(if pawn_files[them][file] == 0 { ROOK_OPEN_FILE } else { ROOK_HALF_OPEN_FILE });
+                }
+            },
+            _ => (),
+        }
+        if let Some(((mg,eg),baseline)) = mobility_weight(piece.kind) {
+            // the tiles it could move to: empty or holding an enemy piece
+            let reach = board.attacks(*piece, *square) & !board.color_bitboard(piece.color);
+            let mobility = reach.count_ones() as i32 - baseline;
+            terms[s].add((mg * mobility, eg * mobility));
+            let near_king = (reach & king_zones[them]).count_ones() as i32;
+            king_attack[them] += near_king * king_attack_weight(piece.kind);
+        }
+    }
+    for color in [Color::WHITE, Color::BLACK] {
+        let s = color.index();
+        if bishops[s] >= 2 {
+            terms[s].add(BISHOP_PAIR);
+        }
+        // counted in the middlegame only, so it fades out as the material comes off
+        let safety = king_shelter(board, color, kings[s], &pawn_files[s])
+            - (king_attack[s] * king_attack[s] / 4).min(500);
+        terms[s].add((safety, 0));
+    }
+    let mg = terms[0].mg - terms[1].mg;
+    let eg = terms[0].eg - terms[1].eg;
+    let phase = phase.min(MAX_PHASE);
+    let score = (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE;
+    match board.player_turn() {
+        Color::WHITE => score,
+        Color::BLACK => -score,
+    }
+}
+
+// the piece on (x,y), with nothing off the edge of the board
+fn piece_at(board: &Board, x: i32, y: i32) -> Option<ChessPiece> {
+    Square::from_coords(x,y).and_then(|square| board.get_piece(square))
+}
+
+fn pawn_terms(board: &Board, pawn: ChessPiece, x: i32, y: i32, pawn_files: &[[i32; 8]; 2]) -> (i32,i32) {
+    let s = pawn.color.index();
+    let file = x as usize;
+    let mut terms = Terms::default();
+    if pawn_files[s][file] > 1 {
+        terms.add(DOUBLED_PAWN);
+    }
+    let neighbours = (file > 0 && pawn_files[s][file-1] > 0) || (file < 7 && pawn_files[s][file+1] > 0);
+    if !neighbours {
+        terms.add(ISOLATED_PAWN);
+    }
+    let dir = match pawn.color { Color::WHITE => 1, Color::BLACK => -1 };
+    // passed when no enemy pawn stands in front of it on its own or a neighbouring file
+    let blocked = (x-1..=x+1).any(|fx| {
+        let mut fy = y + dir;
+        while (0..8).contains(&fy) {
+            if matches!(piece_at(board,fx,fy), Some(ChessPiece{kind:Kind::PAWN,color}) if color != pawn.color) {
+                return true;
+            }
+            fy += dir;
+        }
+        false
+    });
+    if !blocked {
+        terms.add(PASSED_PAWN[relative_rank(pawn.color, y) as usize]);
+    }
+    (terms.mg, terms.eg)
+}
+
+// pawns in front of a castled king, and no open files next to it
+fn king_shelter(board: &Board, color: Color, (kx,ky): (i32,i32), own_pawn_files: &[i32; 8]) -> i32 {
+    if relative_rank(color, ky) > 1 {
+        return 0;
+    }
+    let dir = match color { Color::WHITE => 1, Color::BLACK => -1 };
+    let mut shelter = 0;
+    for fx in (kx-1).max(0)..=(kx+1).min(7) {
+        for step in 1..=2 {
+            if matches!(piece_at(board,fx,ky+step*dir), Some(ChessPiece{kind:Kind::PAWN,color:c}) if c == color) {
+                shelter += PAWN_SHIELD;
+                break;
+            }
+        }
+        if own_pawn_files[fx as usize] == 0 {
+            shelter += OPEN_FILE_NEAR_KING;
+        }
+    }
+    shelter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::board::START_FEN;
+
+    // the same position with the colours swapped and the board turned over
+    fn mirror(fen: &str) -> String {
+        let fields: Vec<&str> = fen.split(' ').collect();
+        let swap_case = |text: &str| text.chars()
+            .map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() })
+            .collect::<String>();
+        let placement: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+        let turn = if fields[1] == "w" { "b" } else { "w" };
+        let mut castling: Vec<char> = swap_case(fields[2]).chars().collect();
+        castling.sort();
+        let en_passant: String = fields[3].chars().map(|c| match c { '3' => '6', '6' => '3', c => c }).collect();
+        format!("{} {} {} {} {} {}", placement.join("/"), turn, castling.iter().collect::<String>(), en_passant, fields[4], fields[5])
+    }
+
+    #[test]
+    fn mirrored_positions_evaluate_the_same() {
+        for fen in [
+            START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            let mirrored = Board::from_fen(&mirror(fen)).unwrap();
+            assert_eq!(evaluate(&board), evaluate(&mirrored), "{}", fen);
+        }
+    }
+}
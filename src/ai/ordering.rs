@@ -0,0 +1,177 @@
+use super::{MAX_PLY,piece_value};
+use super::super::board::Board;
+use super::super::moves::Move;
+use super::super::pieces::{Color,Kind};
+
+// history scores are kept within this, so old successes fade as new ones come in
+const HISTORY_MAX: i32 = 1 << 14;
+
+/// What the search has learnt about which quiet moves tend to cause cutoffs.
+pub(super) struct MoveOrdering {
+    // two quiet moves per ply that recently refuted a sibling position
+    killers: Vec<[Option<Move>; 2]>,
+    // the quiet move that last refuted each previous move, by its from and to tiles
+    counter_moves: Vec<Option<Move>>,
+    // how often a quiet move caused a cutoff, by side, from and to tiles
+    history: Vec<i32>,
+}
+
+impl MoveOrdering {
+    pub(super) fn new() -> Self {
+        Self {
+            killers: vec![[None; 2]; 2 * MAX_PLY as usize + 1],
+            counter_moves: vec![None; 64 * 64],
+            history: vec![0; 2 * 64 * 64],
+        }
+    }
+    fn history_index(color: Color, mv: Move) -> usize {
+        color.index() * 64 * 64 + from_to(mv)
+    }
+    fn history(&self, color: Color, mv: Move) -> i32 {
+        self.history[Self::history_index(color, mv)]
+    }
+    fn counter_move(&self, prev: Option<Move>) -> Option<Move> {
+        prev.and_then(|prev| self.counter_moves[from_to(prev)])
+    }
+    /// Rewards the quiet move `good` that caused a beta cutoff, and penalises the
+    /// quiet moves tried before it.
+    pub(super) fn quiet_cutoff(&mut self, color: Color, ply: u32, prev: Option<Move>, good: Move, tried: &[Move], depth: u32) {
+        let killers = &mut self.killers[ply as usize];
+        if killers[0] != Some(good) {
+            killers[1] = killers[0];
+            killers[0] = Some(good);
+        }
+        if let Some(prev) = prev {
+            self.counter_moves[from_to(prev)] = Some(good);
+        }
+        let bonus = (depth * depth) as i32;
+        self.add_history(color, good, bonus);
+        for bad in tried {
+            self.add_history(color, *bad, -bonus);
+        }
+    }
+    fn add_history(&mut self, color: Color, mv: Move, bonus: i32) {
+        let entry = &mut self.history[Self::history_index(color, mv)];
+        // moving toward the limit more slowly the closer we get to it
+        *entry += bonus - *entry * bonus.abs() / HISTORY_MAX;
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+enum Stage {
+    HashMove,
+    Generate,
+    GoodCaptures,
+    // the killers, then the counter-move
+    Refutations,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+/// Hands out moves best first, one stage at a time: the hash move before anything
+/// is generated, then winning captures by MVV-LVA, killers, the counter-move,
+/// quiet moves by history and finally captures that lose material.
+pub(super) struct MovePicker {
+    stage: Stage,
+    hash_move: Option<Move>,
+    refutations: Vec<Move>,
+    captures: Vec<(Move,i32)>,
+    quiets: Vec<Move>,
+    bad_captures: Vec<Move>,
+}
+
+impl MovePicker {
+    pub(super) fn new(hash_move: Option<Move>, ordering: &MoveOrdering, ply: u32, prev: Option<Move>) -> Self {
+        let mut refutations: Vec<Move> = ordering.killers[ply as usize].iter().flatten().copied().collect();
+        refutations.extend(ordering.counter_move(prev));
+        Self {
+            stage: Stage::HashMove,
+            hash_move,
+            refutations,
+            captures: Vec::<(Move,i32)>::new(),
+            quiets: Vec::<Move>::new(),
+            bad_captures: Vec::<Move>::new(),
+        }
+    }
+    pub(super) fn next(&mut self, board: &Board, ordering: &MoveOrdering) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::Generate;
+                    // the table can hand us a move from a different position that shares the slot
+                    self.hash_move = self.hash_move.filter(|mv| board.is_legal(*mv));
+                    if self.hash_move.is_some() {
+                        return self.hash_move;
+                    }
+                },
+                Stage::Generate => {
+                    self.stage = Stage::GoodCaptures;
+                    for mv in board.legal_moves() {
+                        if Some(mv) == self.hash_move {
+                            continue;
+                        }
+                        match board.captured_piece(mv) {
+                            Some(captured) => self.captures.push((mv, mvv_lva(board, mv, captured.kind))),
+                            None if mv.is_promotion() => self.captures.push((mv, mvv_lva(board, mv, Kind::PAWN))),
+                            None => self.quiets.push(mv),
+                        }
+                    }
+                    self.refutations.retain(|mv| Some(*mv) != self.hash_move);
+                    self.refutations.dedup();
+                },
+                Stage::GoodCaptures => {
+                    let Some(best) = (0..self.captures.len()).max_by_key(|i| self.captures[*i].1) else {
+                        self.stage = Stage::Refutations;
+                        continue;
+                    };
+                    let (mv,_) = self.captures.swap_remove(best);
+                    if board.see(mv, piece_value) < 0 {
+                        self.bad_captures.push(mv);
+                        continue;
+                    }
+                    return Some(mv);
+                },
+                Stage::Refutations => {
+                    if self.refutations.is_empty() {
+                        self.stage = Stage::Quiets;
+                        continue;
+                    }
+                    let mv = self.refutations.remove(0);
+                    // only if it is a legal quiet move here too
+                    if let Some(index) = self.quiets.iter().position(|quiet| *quiet == mv) {
+                        return Some(self.quiets.swap_remove(index));
+                    }
+                },
+                Stage::Quiets => {
+                    let color = board.player_turn();
+                    let Some(best) = (0..self.quiets.len()).max_by_key(|i| ordering.history(color, self.quiets[*i])) else {
+                        self.stage = Stage::BadCaptures;
+                        continue;
+                    };
+                    return Some(self.quiets.swap_remove(best));
+                },
+                Stage::BadCaptures => {
+                    if self.bad_captures.is_empty() {
+                        self.stage = Stage::Done;
+                        continue;
+                    }
+                    return Some(self.bad_captures.remove(0));
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+}
+
+// the from and to tiles as one index, for tables kept per move
+fn from_to(mv: Move) -> usize {
+    mv.from_square().index() * 64 + mv.to_square().index()
+}
+
+// most valuable victim first, least valuable attacker breaking ties
+fn mvv_lva(board: &Board, mv: Move, victim: Kind) -> i32 {
+    let attacker = board.get_piece(mv.from_square()).expect("~no piece to move").kind;
+    let promotion = mv.promotion().map_or(0, piece_value);
+    piece_value(victim) * 10 - piece_value(attacker) / 10 + promotion
+}
@@ -0,0 +1,189 @@
+use super::{MATE,MAX_PLY};
+use super::super::moves::Move;
+use std::sync::atomic::{AtomicU64,Ordering};
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Bound {
+    Exact,
+    /// The score is at least this much (the search failed high).
+    Lower,
+    /// The score is at most this much (the search failed low).
+    Upper,
+}
+
+#[derive(Clone,Copy,Debug)]
+pub struct Entry {
+    key: u64,
+    pub best_move: Option<Move>,
+    score: i32,
+    pub depth: u32,
+    pub bound: Bound,
+    age: u8,
+}
+impl Entry {
+    /// The stored score, with mate distances counted from `ply` again.
+    pub fn score(&self, ply: u32) -> i32 {
+        if self.score >= MATE - MAX_PLY as i32 {
+            self.score - ply as i32
+        }
+        else if self.score <= -(MATE - MAX_PLY as i32) {
+            self.score + ply as i32
+        }
+        else {
+            self.score
+        }
+    }
+}
+
+/// Search results of earlier positions, keyed by Zobrist hash. Kept between
+/// searches so the next move can start from what was already found.
+///
+/// Every search thread reads and writes the table at once without locking.
+/// Each slot keeps the key xored with the data, so an entry torn by two
+/// threads writing together no longer matches its key and is just ignored.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    age: u8,
+}
+
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+// data bits: the move in 0-15, score 16-47, depth 48-55, bound 56-57 and age 58-63
+const AGE_MASK: u8 = 0x3f;
+
+impl TranspositionTable {
+    pub const DEFAULT_MB: usize = 16;
+
+    pub fn new(megabytes: usize) -> Self {
+        let count = (megabytes.max(1) * 1024 * 1024 / std::mem::size_of::<Slot>()).max(1);
+        Self {
+            slots: (0..count).map(|_| Slot::default()).collect(),
+            age: 0,
+        }
+    }
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            *slot = Slot::default();
+        }
+        self.age = 0;
+    }
+    /// Marks the start of a new search, so entries left from earlier ones get replaced first.
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1) & AGE_MASK;
+    }
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(key)];
+        let data = slot.data.load(Ordering::Relaxed);
+        if slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        unpack(key, data)
+    }
+    pub fn store(&self, key: u64, best_move: Option<Move>, score: i32, depth: u32, bound: Bound, ply: u32) {
+        let slot = &self.slots[self.index(key)];
+        let old_data = slot.data.load(Ordering::Relaxed);
+        let old_key = slot.key.load(Ordering::Relaxed) ^ old_data;
+        let old = unpack(old_key, old_data);
+        // keep deeper results from this search over shallower ones
+        if let Some(old) = old {
+            if old.key != key && old.age == self.age && old.depth > depth {
+                return;
+            }
+        }
+        // mate scores are stored as distance from this position, not from the root
+        let score = if score >= MATE - MAX_PLY as i32 {
+            score + ply as i32
+        }
+        else if score <= -(MATE - MAX_PLY as i32) {
+            score - ply as i32
+        }
+        else {
+            score
+        };
+        // a search that found no move shouldn't forget the one we had
+        let best_move = best_move.or(old.filter(|old| old.key == key).and_then(|old| old.best_move));
+        let data = pack(&Entry { key, best_move, score, depth, bound, age: self.age });
+        slot.data.store(data, Ordering::Relaxed);
+        slot.key.store(key ^ data, Ordering::Relaxed);
+    }
+    /// How full the table is in permille, from a sample of its entries.
+    pub fn hashfull(&self) -> u32 {
+        let sample = self.slots.len().min(1000);
+        let used = self.slots[..sample].iter()
+            .filter_map(|slot| unpack(0, slot.data.load(Ordering::Relaxed)))
+            .filter(|entry| entry.age == self.age)
+            .count();
+        (used * 1000 / sample) as u32
+    }
+    fn index(&self, key: u64) -> usize {
+        (key % self.slots.len() as u64) as usize
+    }
+}
+
+fn pack(entry: &Entry) -> u64 {
+    let best_move = entry.best_move.map_or(0, Move::bits);
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+    best_move as u64
+        | (entry.score as u32 as u64) << 16
+        | (entry.depth.min(255) as u64) << 48
+        | bound << 56
+        | ((entry.age & AGE_MASK) as u64) << 58
+}
+
+// empty slots have no bound
+fn unpack(key: u64, data: u64) -> Option<Entry> {
+    let bound = match data >> 56 & 3 {
+        1 => Bound::Exact,
+        2 => Bound::Lower,
+        3 => Bound::Upper,
+        _ => return None,
+    };
+    let best_move = match data as u16 {
+        0 => None,
+        bits => Some(Move::from_bits(bits)),
+    };
+    Some(Entry {
+        key,
+        best_move,
+        score: (data >> 16) as u32 as i32,
+        depth: (data >> 48 & 0xff) as u32,
+        bound,
+        age: (data >> 58) as u8,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::pieces::Kind;
+    use super::super::super::square::Square;
+
+    #[test]
+    fn entries_round_trip() {
+        let mut tt = TranspositionTable::new(1);
+        tt.new_search();
+        let square = |name: &str| name.parse::<Square>().unwrap();
+        let promotion = Move::new(square("b7"), square("a8"), Move::promotion_flags(Kind::KNIGHT) | Move::CAPTURE);
+        let stored = [
+            (1, Some(promotion), -350, 7, Bound::Upper),
+            (2, None, MATE - 5, 12, Bound::Exact),
+            (3, Some(Move::new(square("e1"), square("g1"), Move::KING_CASTLE)), -(MATE - 8), 1, Bound::Lower),
+        ];
+        for (key, best_move, score, depth, bound) in stored {
+            tt.store(key, best_move, score, depth, bound, 3);
+            let entry = tt.probe(key).expect("entry was just stored");
+            assert_eq!((entry.best_move, entry.score(3), entry.depth, entry.bound), (best_move, score, depth, bound));
+        }
+        assert!(tt.probe(4).is_none());
+        tt.clear();
+        assert!(tt.probe(1).is_none());
+    }
+}
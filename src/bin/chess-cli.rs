@@ -0,0 +1,125 @@
+// headless tools: move generation counts, search speed and PGN checking
+use chess::ai::{self,SearchLimits,TranspositionTable};
+use chess::board::{Board,START_FEN};
+use chess::notation;
+use chess::pgn;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration,Instant};
+
+const USAGE: &str = "usage: chess-cli perft <depth> [fen] | chess-cli bench [depth] [threads] | chess-cli pgn <file>";
+const BENCH_DEPTH: u32 = 5;
+// a spread of openings, middlegames and endgames for measuring search speed
+const BENCH_POSITIONS: [&str; 6] = [
+    START_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+fn main() {
+    chess::board::init();
+    let args: Vec<String> = std::env::args().collect();
+    match args.as_slice() {
+        [_, command, path] if command == "pgn" => check_pgn(path),
+        [_, command, depth, fen @ ..] if command == "perft" => {
+            let fen = if fen.is_empty() { START_FEN.to_string() } else { fen.join(" ") };
+            let board = match Board::from_fen(&fen) {
+                Ok(board) => board,
+                Err(err) => {
+                    eprintln!("invalid FEN: {}", err);
+                    return;
+                }
+            };
+            match depth.parse() {
+                Ok(depth) => perft(&board, depth),
+                Err(_) => eprintln!("perft depth should be a number"),
+            }
+        },
+        [_, command, args @ ..] if command == "bench" && args.len() <= 2 => {
+            let depth = args.first().map_or(Ok(BENCH_DEPTH), |depth| depth.parse());
+            let threads = args.get(1).map_or(Ok(1), |threads| threads.parse());
+            match (depth, threads) {
+                (Ok(depth), Ok(threads)) => bench(depth, threads),
+                _ => eprintln!("bench depth and threads should be numbers"),
+            }
+        },
+        _ => eprintln!("{}", USAGE),
+    }
+}
+
+fn perft(board: &Board, depth: u32) {
+    let mut total = 0;
+    for (ply, count) in board.divide(depth) {
+        println!("{}: {}", notation::uci(ply), count);
+        total += count;
+    }
+    println!("\nNodes searched: {}", total);
+}
+
+// searches every bench position to a fixed depth and reports the nodes per second; with
+// more than one thread it runs single-threaded first too and compares the time to each depth
+fn bench(depth: u32, threads: usize) {
+    let threads = threads.max(1);
+    let single = (threads > 1).then(|| bench_run(depth, 1));
+    let (nodes, time, time_to_depth) = bench_run(depth, threads);
+    println!("\nTime to depth ({} thread{}):", threads, if threads > 1 { "s" } else { "" });
+    for (index, total) in time_to_depth.iter().enumerate() {
+        match &single {
+            Some((_, _, single)) => println!("{:>4} {:>8} ms  1 thread {:>8} ms  speedup {:.2}", index + 1, total.as_millis(),
+                                             single[index].as_millis(), single[index].as_secs_f64() / total.as_secs_f64().max(0.001)),
+            None => println!("{:>4} {:>8} ms", index + 1, total.as_millis()),
+        }
+    }
+    println!("\nNodes searched: {}", nodes);
+    println!("Time: {} ms", time.as_millis());
+    println!("Nodes/second: {}", (nodes as f64 / time.as_secs_f64().max(0.001)) as u64);
+}
+
+// (nodes, time, time summed over all positions at which each depth finished)
+fn bench_run(depth: u32, threads: usize) -> (u64, Duration, Vec<Duration>) {
+    let stop = AtomicBool::new(false);
+    let mut nodes = 0;
+    let mut time = Duration::ZERO;
+    let mut time_to_depth = vec![Duration::ZERO; depth as usize];
+    for fen in BENCH_POSITIONS {
+        let board = Board::from_fen(fen).expect("~bench positions are valid");
+        let mut tt = TranspositionTable::new(TranspositionTable::DEFAULT_MB);
+        let start = Instant::now();
+        let result = ai::search(&board, &SearchLimits { threads, ..SearchLimits::depth(depth) }, &mut tt, &stop, &mut |info| {
+            if let Some(total) = time_to_depth.get_mut(info.depth as usize - 1) {
+                *total += info.time;
+            }
+        });
+        let elapsed = start.elapsed();
+        println!("{:>10} nodes {:>8} ms  {} thread{}  {}", result.nodes, elapsed.as_millis(), threads, if threads > 1 { "s" } else { "" }, fen);
+        nodes += result.nodes;
+        time += elapsed;
+    }
+    (nodes, time, time_to_depth)
+}
+
+fn check_pgn(path: &str) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("couldn't read {}: {}", path, err);
+            return;
+        }
+    };
+    match pgn::parse_pgn(&text) {
+        Ok(games) => {
+            for game in games {
+                let final_board = game.final_board().expect("moves were checked while parsing");
+                println!("{} - {} {} ({} plies) {}",
+                         game.tag("White").unwrap_or("?"),
+                         game.tag("Black").unwrap_or("?"),
+                         game.tag("Result").unwrap_or("*"),
+                         game.moves.len(),
+                         final_board.to_fen());
+            }
+        },
+        Err(err) => eprintln!("invalid PGN: {}", err),
+    }
+}
@@ -0,0 +1,195 @@
+// Universal Chess Interface front end, so the engine can be used from any chess GUI
+use chess::ai::{self,Score,SearchInfo,SearchLimits,TranspositionTable};
+use chess::board::Board;
+use chess::notation;
+use std::io::BufRead;
+use std::sync::{Arc,Mutex};
+use std::sync::atomic::{AtomicBool,Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const ENGINE_NAME: &str = "chess";
+const ENGINE_AUTHOR: &str = "nathan-08";
+const MAX_THREADS: usize = 256;
+
+struct Engine {
+    board: Board,
+    move_overhead: Duration,
+    threads: usize,
+    tt: Arc<Mutex<TranspositionTable>>,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+impl Engine {
+    fn new() -> Self {
+        Self {
+            board: Board::default(),
+            move_overhead: Duration::from_millis(10),
+            threads: 1,
+            tt: Arc::new(Mutex::new(TranspositionTable::new(TranspositionTable::DEFAULT_MB))),
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        }
+    }
+    // returns false once the GUI asks us to quit
+    fn handle(&mut self, line: &str) -> bool {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("uci") => {
+                println!("id name {}", ENGINE_NAME);
+                println!("id author {}", ENGINE_AUTHOR);
+                println!("option name Hash type spin default {} min 1 max 4096", TranspositionTable::DEFAULT_MB);
+                println!("option name Clear Hash type button");
+                println!("option name Move Overhead type spin default 10 min 0 max 5000");
+                println!("option name Threads type spin default 1 min 1 max {}", MAX_THREADS);
+                println!("uciok");
+            },
+            Some("isready") => println!("readyok"),
+            Some("ucinewgame") => {
+                self.stop_search();
+                self.board = Board::default();
+                self.tt.lock().expect("~tt lock poisoned").clear();
+            },
+            Some("position") => {
+                self.stop_search();
+                let words: Vec<&str> = words.collect();
+                match parse_position(&words) {
+                    Some(board) => self.board = board,
+                    None => eprintln!("invalid position: {}", line),
+                }
+            },
+            Some("go") => {
+                self.stop_search();
+                let words: Vec<&str> = words.collect();
+                let limits = SearchLimits { threads: self.threads, ..parse_go(&words, self.move_overhead) };
+                self.start_search(limits);
+            },
+            Some("stop") => self.stop_search(),
+            Some("setoption") => self.set_option(line),
+            Some("quit") => {
+                self.stop_search();
+                return false;
+            },
+            // debug, register and ponderhit need no answer from us
+            _ => (),
+        }
+        true
+    }
+    fn set_option(&mut self, line: &str) {
+        let rest = line.trim_start_matches("setoption").trim();
+        let Some(rest) = rest.strip_prefix("name") else { return };
+        let (name,value) = match rest.split_once(" value ") {
+            Some((name,value)) => (name.trim(), value.trim()),
+            None => (rest.trim(), ""),
+        };
+        self.stop_search();
+        if name.eq_ignore_ascii_case("Hash") {
+            match value.parse() {
+                Ok(megabytes) => *self.tt.lock().expect("~tt lock poisoned") = TranspositionTable::new(megabytes),
+                Err(_) => eprintln!("invalid Hash: {}", value),
+            }
+        }
+        else if name.eq_ignore_ascii_case("Clear Hash") {
+            self.tt.lock().expect("~tt lock poisoned").clear();
+        }
+        else if name.eq_ignore_ascii_case("Move Overhead") {
+            match value.parse() {
+                Ok(millis) => self.move_overhead = Duration::from_millis(millis),
+                Err(_) => eprintln!("invalid Move Overhead: {}", value),
+            }
+        }
+        else if name.eq_ignore_ascii_case("Threads") {
+            match value.parse::<usize>() {
+                Ok(threads) => self.threads = threads.clamp(1, MAX_THREADS),
+                Err(_) => eprintln!("invalid Threads: {}", value),
+            }
+        }
+        else {
+            eprintln!("unknown option: {}", name);
+        }
+    }
+    fn start_search(&mut self, limits: SearchLimits) {
+        self.stop.store(false, Ordering::Relaxed);
+        let board = self.board.clone();
+        let stop = Arc::clone(&self.stop);
+        let tt = Arc::clone(&self.tt);
+        self.search = Some(std::thread::spawn(move || {
+            let mut tt = tt.lock().expect("~tt lock poisoned");
+            let result = ai::search(&board, &limits, &mut tt, &stop, &mut |info| print_info(info));
+            // "go infinite" must not answer until it is told to stop
+            while limits.infinite && !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            match result.best_move {
+                Some(ply) => println!("bestmove {}", notation::uci(ply)),
+                None => println!("bestmove 0000"),
+            }
+        }));
+    }
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            search.join().expect("~search thread panicked");
+        }
+    }
+}
+
+fn print_info(info: &SearchInfo) {
+    let score = match info.score {
+        Score::Centipawns(cp) => format!("cp {}", cp),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let pv: Vec<String> = info.pv.iter().map(|ply| notation::uci(*ply)).collect();
+    println!("info depth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+             info.depth, score, info.nodes, info.nps(), info.hashfull, info.time.as_millis(), pv.join(" "));
+}
+
+// position [startpos | fen <fen>] [moves <move>...]
+fn parse_position(words: &[&str]) -> Option<Board> {
+    let moves_at = words.iter().position(|word| *word == "moves").unwrap_or(words.len());
+    let mut board = match words.first() {
+        Some(&"startpos") => Board::default(),
+        Some(&"fen") => Board::from_fen(&words[1..moves_at].join(" ")).ok()?,
+        _ => return None,
+    };
+    for word in words.iter().skip(moves_at+1) {
+        let ply = notation::parse_uci(&board, word)?;
+        board.perform_move_2(ply);
+    }
+    Some(board)
+}
+
+fn parse_go(words: &[&str], move_overhead: Duration) -> SearchLimits {
+    let mut limits = SearchLimits { move_overhead, ..Default::default() };
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        // an overdrawn clock is sent as a negative time, which means no time left
+        let mut number = || words.next().and_then(|value| value.parse::<i64>().ok()).map(|value| value.max(0) as u64);
+        match *word {
+            "depth" => limits.depth = number().map(|depth| depth as u32),
+            "nodes" => limits.nodes = number(),
+            "movetime" => limits.movetime = number().map(Duration::from_millis),
+            "wtime" => limits.wtime = number().map(Duration::from_millis),
+            "btime" => limits.btime = number().map(Duration::from_millis),
+            "winc" => limits.winc = number().map(Duration::from_millis),
+            "binc" => limits.binc = number().map(Duration::from_millis),
+            "movestogo" => limits.movestogo = number().map(|moves| moves as u32),
+            "infinite" | "ponder" => limits.infinite = true,
+            _ => (),
+        }
+    }
+    limits
+}
+
+fn main() {
+    chess::board::init();
+    let mut engine = Engine::new();
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if !engine.handle(&line) {
+            return;
+        }
+    }
+    engine.stop_search();
+}
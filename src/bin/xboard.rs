@@ -0,0 +1,287 @@
+// Chess Engine Communication Protocol (xboard/winboard) front end
+use chess::ai::{self,Score,SearchInfo,SearchLimits,TranspositionTable};
+use chess::board::Board;
+use chess::moves::Move;
+use chess::notation;
+use chess::pgn;
+use chess::pieces::Color;
+use std::io::BufRead;
+use std::sync::{Arc,Mutex};
+use std::sync::atomic::{AtomicBool,Ordering};
+use std::sync::mpsc::{self,Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const ENGINE_NAME: &str = "chess";
+const MAX_THREADS: usize = 256;
+
+enum Event {
+    Command(String),
+    // which search finished, so stale results can be thrown away
+    BestMove(u32,Option<Move>),
+}
+
+#[derive(Default)]
+struct TimeControl {
+    moves_per_session: u32,
+    base: Duration,
+    increment: Duration,
+    seconds_per_move: Option<Duration>,
+    depth: Option<u32>,
+    engine_clock: Option<Duration>,
+    opponent_clock: Option<Duration>,
+}
+
+struct Engine {
+    board: Board,
+    history: Vec<Board>,
+    engine_color: Option<Color>,
+    post: bool,
+    time_control: TimeControl,
+    tt: Arc<Mutex<TranspositionTable>>,
+    events: Sender<Event>,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+    search_id: u32,
+    threads: usize,
+}
+
+impl Engine {
+    fn new(events: Sender<Event>) -> Self {
+        Self {
+            board: Board::default(),
+            history: Vec::<Board>::new(),
+            engine_color: Some(Color::BLACK),
+            post: false,
+            time_control: TimeControl::default(),
+            tt: Arc::new(Mutex::new(TranspositionTable::new(TranspositionTable::DEFAULT_MB))),
+            events,
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+            search_id: 0,
+            threads: 1,
+        }
+    }
+    // returns false once the GUI asks us to quit
+    fn handle(&mut self, line: &str) -> bool {
+        let (command,args) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+        let args = args.trim();
+        match command {
+            "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer" | "name" | "rating" | "draw" => (),
+            "protover" => {
+                println!("feature myname=\"{}\" ping=1 setboard=1 usermove=1 san=0 colors=0 sigint=0 sigterm=0 analyze=0 reuse=1 memory=1 smp=1", ENGINE_NAME);
+                println!("feature done=1");
+            },
+            "ping" => println!("pong {}", args),
+            "new" => {
+                self.cancel_search();
+                self.board = Board::default();
+                self.history.clear();
+                self.engine_color = Some(Color::BLACK);
+                self.time_control.depth = None;
+                self.tt.lock().expect("~tt lock poisoned").clear();
+            },
+            "force" | "result" => {
+                self.cancel_search();
+                self.engine_color = None;
+            },
+            "go" => {
+                self.cancel_search();
+                self.engine_color = Some(self.board.player_turn());
+            },
+            // move now: finish the search early but keep its answer
+            "?" => self.stop.store(true, Ordering::Relaxed),
+            "usermove" => {
+                self.cancel_search();
+                match notation::parse_uci(&self.board, args) {
+                    Some(ply) => self.play(ply),
+                    None => println!("Illegal move: {}", args),
+                }
+            },
+            "undo" => {
+                self.cancel_search();
+                self.take_back(1);
+            },
+            "remove" => {
+                self.cancel_search();
+                self.take_back(2);
+            },
+            "setboard" => {
+                self.cancel_search();
+                match Board::from_fen(args) {
+                    Ok(board) => {
+                        self.board = board;
+                        self.history.clear();
+                    },
+                    Err(err) => println!("tellusererror Illegal position: {}", err),
+                }
+            },
+            "level" => self.set_level(args),
+            "st" => self.time_control.seconds_per_move = args.parse().ok().map(Duration::from_secs),
+            "sd" => self.time_control.depth = args.parse().ok(),
+            "time" => self.time_control.engine_clock = parse_centiseconds(args),
+            "otim" => self.time_control.opponent_clock = parse_centiseconds(args),
+            "memory" => {
+                self.cancel_search();
+                match args.parse() {
+                    Ok(megabytes) => *self.tt.lock().expect("~tt lock poisoned") = TranspositionTable::new(megabytes),
+                    Err(_) => println!("Error (bad memory): {}", args),
+                }
+            },
+            "cores" => match args.parse::<usize>() {
+                Ok(cores) => self.threads = cores.clamp(1, MAX_THREADS),
+                Err(_) => println!("Error (bad cores): {}", args),
+            },
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "quit" => {
+                self.cancel_search();
+                return false;
+            },
+            _ => println!("Error (unknown command): {}", command),
+        }
+        self.think_if_our_turn();
+        true
+    }
+    // level MPS BASE INC, where BASE is minutes or minutes:seconds
+    fn set_level(&mut self, args: &str) {
+        let fields: Vec<&str> = args.split_whitespace().collect();
+        let [moves,base,increment] = fields.as_slice() else {
+            println!("Error (bad level): {}", args);
+            return;
+        };
+        let base = match base.split_once(':') {
+            Some((minutes,seconds)) => minutes.parse::<u64>().unwrap_or(0) * 60 + seconds.parse::<u64>().unwrap_or(0),
+            None => base.parse::<u64>().unwrap_or(0) * 60,
+        };
+        self.time_control.moves_per_session = moves.parse().unwrap_or(0);
+        self.time_control.base = Duration::from_secs(base);
+        self.time_control.increment = Duration::from_secs_f64(increment.parse().unwrap_or(0.0));
+        self.time_control.seconds_per_move = None;
+    }
+    fn play(&mut self, mv: Move) {
+        self.history.push(self.board.clone());
+        self.board.perform_move_2(mv);
+        if self.board.result.is_some() {
+            println!("{} {{{}}}", pgn::result_str(self.board.result), self.board.turn_str());
+        }
+    }
+    fn take_back(&mut self, plies: usize) {
+        for _ in 0..plies {
+            if let Some(board) = self.history.pop() {
+                self.board = board;
+            }
+        }
+    }
+    fn limits(&self) -> SearchLimits {
+        let time_control = &self.time_control;
+        if let Some(movetime) = time_control.seconds_per_move {
+            return SearchLimits { depth: time_control.depth, movetime: Some(movetime), ..Default::default() };
+        }
+        let Some(clock) = time_control.engine_clock.or((!time_control.base.is_zero()).then_some(time_control.base)) else {
+            // with no time control at all just search to the GUI's depth
+            return SearchLimits::depth(time_control.depth.unwrap_or(3));
+        };
+        let opponent_clock = time_control.opponent_clock.unwrap_or(clock);
+        let (wtime,btime) = match self.board.player_turn() {
+            Color::WHITE => (clock, opponent_clock),
+            Color::BLACK => (opponent_clock, clock),
+        };
+        let movestogo = (time_control.moves_per_session > 0).then(|| {
+            let played = self.board.fullmove_number - 1;
+            time_control.moves_per_session - played % time_control.moves_per_session
+        });
+        SearchLimits {
+            depth: time_control.depth,
+            wtime: Some(wtime),
+            btime: Some(btime),
+            winc: Some(time_control.increment),
+            binc: Some(time_control.increment),
+            movestogo,
+            ..Default::default()
+        }
+    }
+    fn think_if_our_turn(&mut self) {
+        if self.search.is_some() || self.engine_color != Some(self.board.player_turn()) || self.board.result.is_some() {
+            return;
+        }
+        self.stop.store(false, Ordering::Relaxed);
+        self.search_id += 1;
+        let id = self.search_id;
+        let board = self.board.clone();
+        let limits = SearchLimits { threads: self.threads, ..self.limits() };
+        let post = self.post;
+        let stop = Arc::clone(&self.stop);
+        let tt = Arc::clone(&self.tt);
+        let events = self.events.clone();
+        self.search = Some(std::thread::spawn(move || {
+            let mut tt = tt.lock().expect("~tt lock poisoned");
+            let result = ai::search(&board, &limits, &mut tt, &stop, &mut |info| if post { print_thinking(info) });
+            // the main loop may already be gone when we quit mid search
+            let _ = events.send(Event::BestMove(id, result.best_move));
+        }));
+    }
+    fn finish_search(&mut self, id: u32, best_move: Option<Move>) {
+        if id != self.search_id {
+            return;
+        }
+        if let Some(search) = self.search.take() {
+            search.join().expect("~search thread panicked");
+        }
+        if let Some(ply) = best_move {
+            println!("move {}", notation::uci(ply));
+            self.play(ply);
+        }
+        self.think_if_our_turn();
+    }
+    // stop thinking without playing the move that was found
+    fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            search.join().expect("~search thread panicked");
+            self.search_id += 1;
+        }
+    }
+}
+
+// an overdrawn clock is sent as a negative time, which means no time left
+fn parse_centiseconds(text: &str) -> Option<Duration> {
+    text.parse().ok().map(|centis: i64| Duration::from_millis(centis.max(0) as u64 * 10))
+}
+
+// ply score time nodes pv, with time in centiseconds
+fn print_thinking(info: &SearchInfo) {
+    let score = match info.score {
+        Score::Centipawns(cp) => cp,
+        Score::Mate(moves) if moves > 0 => 100_000 + moves,
+        Score::Mate(moves) => -100_000 + moves,
+    };
+    let pv: Vec<String> = info.pv.iter().map(|ply| notation::uci(*ply)).collect();
+    println!("{} {} {} {} {}", info.depth, score, info.time.as_millis() / 10, info.nodes, pv.join(" "));
+}
+
+fn main() {
+    chess::board::init();
+    let (sender,receiver) = mpsc::channel();
+    let commands = sender.clone();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if commands.send(Event::Command(line)).is_err() {
+                break;
+            }
+        }
+        let _ = commands.send(Event::Command("quit".to_string()));
+    });
+    let mut engine = Engine::new(sender);
+    for event in receiver {
+        match event {
+            Event::Command(line) => {
+                if !engine.handle(&line) {
+                    return;
+                }
+            },
+            Event::BestMove(id,best_move) => engine.finish_search(id, best_move),
+        }
+    }
+}
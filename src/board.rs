@@ -0,0 +1,739 @@
+use super::pieces::{ChessPiece, Color, Kind};
+use super::moves::Move;
+use super::square::Square;
+
+mod attacks;
+mod bitboard;
+mod fen;
+mod perft;
+mod see;
+mod zobrist;
+
+pub use bitboard::{Bitboard,squares};
+pub use fen::{FenError,START_FEN};
+
+use bitboard::State;
+
+pub const PROMOTION_KINDS: [Kind; 4] = [Kind::QUEEN, Kind::ROOK, Kind::BISHOP, Kind::KNIGHT];
+
+pub type Attackers = Vec<(ChessPiece,Square)>;
+
+const KNIGHT_OFFSETS: [(i32,i32); 8] = [(2,1),(2,-1),(-2,1),(-2,-1),(1,2),(1,-2),(-1,2),(-1,-2)];
+const KING_OFFSETS: [(i32,i32); 8] = [(1,1),(1,0),(1,-1),(-1,1),(-1,0),(-1,-1),(0,1),(0,-1)];
+const ROOK_DIRECTIONS: [(i32,i32); 4] = [(1,0),(-1,0),(0,1),(0,-1)];
+const BISHOP_DIRECTIONS: [(i32,i32); 4] = [(1,1),(1,-1),(-1,1),(-1,-1)];
+// b1, d1 and so on: the tiles where x + y is odd
+const LIGHT_SQUARES: Bitboard = 0x55AA_55AA_55AA_55AA;
+
+/// Builds the slider attack tables up front, so that cost doesn't land in the first
+/// move generation, which may be inside a timed search or on the UI thread.
+pub fn init() {
+    attacks::init();
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum DrawReason {
+    Stalemate,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    InsufficientMaterial,
+}
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+impl GameResult {
+    pub fn win_for(color: Color) -> Self {
+        match color {
+            Color::WHITE => GameResult::WhiteWins,
+            Color::BLACK => GameResult::BlackWins,
+        }
+    }
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            GameResult::WhiteWins => Some(Color::WHITE),
+            GameResult::BlackWins => Some(Color::BLACK),
+            GameResult::Draw(_) => None,
+        }
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+impl CastlingRights {
+    pub fn all() -> Self {
+        Self {
+            white_kingside: true,
+            white_queenside: true,
+            black_kingside: true,
+            black_queenside: true,
+        }
+    }
+    pub fn kingside(&self, color:Color) -> bool {
+        match color {
+            Color::WHITE => self.white_kingside,
+            Color::BLACK => self.black_kingside,
+        }
+    }
+    pub fn queenside(&self, color:Color) -> bool {
+        match color {
+            Color::WHITE => self.white_queenside,
+            Color::BLACK => self.black_queenside,
+        }
+    }
+    fn remove(&mut self, color:Color) {
+        match color {
+            Color::WHITE => { self.white_kingside = false; self.white_queenside = false; },
+            Color::BLACK => { self.black_kingside = false; self.black_queenside = false; },
+        }
+    }
+    // a rook leaving or being captured on its starting corner loses that side
+    fn remove_for_square(&mut self, square:Square) {
+        match square.coords() {
+            (7,0) => self.white_kingside = false,
+            (0,0) => self.white_queenside = false,
+            (7,7) => self.black_kingside = false,
+            (0,7) => self.black_queenside = false,
+            _ => (),
+        }
+    }
+}
+
+// what make_move can't work out again when the move is taken back
+#[derive(Clone,Copy,Debug)]
+struct Undo {
+    mv: Move,
+    captured: Option<ChessPiece>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    hash: u64,
+}
+
+#[derive(Clone)]
+pub struct Board {
+    state: State,
+    pub selected_tile: Option<Square>,
+    // everything that goes into the hash is private, so it can only change along with it
+    player_turn: Color,
+    pub result: Option<GameResult>,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    pub fullmove_number: u32,
+    hash: u64,
+    // the hash of every position so far, for the repetition rules
+    position_history: Vec<u64>,
+    undo_stack: Vec<Undo>,
+}
+impl Default for Board {
+    fn default() -> Self {
+        let mut board = Self {
+            state: get_initial_state(),
+            selected_tile: None,
+            player_turn: Color::WHITE,
+            result: None,
+            castling_rights: CastlingRights::all(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            position_history: Vec::<u64>::new(),
+            undo_stack: Vec::<Undo>::new(),
+        };
+        board.hash = board.compute_hash();
+        board.position_history.push(board.hash);
+        board
+    }
+}
+impl Board {
+    pub fn turn_str(&self) -> &str {
+        if let Some(result) = self.result {
+            match result {
+                GameResult::WhiteWins => "Checkmate, white wins!",
+                GameResult::BlackWins => "Checkmate, black wins!",
+                GameResult::Draw(DrawReason::Stalemate) => "Draw by stalemate",
+                GameResult::Draw(DrawReason::FiftyMoveRule) => "Draw by the fifty-move rule",
+                GameResult::Draw(DrawReason::SeventyFiveMoveRule) => "Draw by the seventy-five-move rule",
+                GameResult::Draw(DrawReason::ThreefoldRepetition) => "Draw by threefold repetition",
+                GameResult::Draw(DrawReason::FivefoldRepetition) => "Draw by fivefold repetition",
+                GameResult::Draw(DrawReason::InsufficientMaterial) => "Draw by insufficient material",
+            }
+        }
+        else {
+            match self.player_turn {
+                Color::WHITE => "White's turn",
+                Color::BLACK => "Black's turn",
+            }
+        }
+    }
+    pub fn player_turn(&self) -> Color {
+        self.player_turn
+    }
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+    /// The tile a pawn that just moved two tiles skipped over.
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+    /// Plies since the last capture or pawn move.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+    pub fn get_piece(&self, square:Square) -> Option<ChessPiece> {
+        self.state.get(square)
+    }
+    pub fn get_pieces(&self) -> Vec<(ChessPiece,Square)> {
+        self.pieces_on(self.state.occupied())
+    }
+    pub fn get_player_pieces(&self,color:Color) -> Vec<(ChessPiece,Square)> {
+        self.pieces_on(self.state.color(color))
+    }
+    fn pieces_on(&self, bitboard:Bitboard) -> Vec<(ChessPiece,Square)> {
+        squares(bitboard)
+            .map(|square| (self.get_piece(square).expect("~bitboard and mailbox disagree"),square))
+            .collect()
+    }
+    /// The tiles holding `color`'s pieces of `kind`.
+    pub fn bitboard(&self, color:Color, kind:Kind) -> Bitboard {
+        self.state.pieces(color,kind)
+    }
+    /// The tiles holding any of `color`'s pieces.
+    pub fn color_bitboard(&self, color:Color) -> Bitboard {
+        self.state.color(color)
+    }
+    /// The tiles `piece` would attack from `square` with the pieces as they stand,
+    /// own pieces included.
+    pub fn attacks(&self, piece:ChessPiece, square:Square) -> Bitboard {
+        let occupied = self.state.occupied();
+        match piece.kind {
+            Kind::PAWN => attacks::pawn_attacks(piece.color,square),
+            Kind::KNIGHT => attacks::knight_attacks(square),
+            Kind::BISHOP => attacks::bishop_attacks(square,occupied),
+            Kind::ROOK => attacks::rook_attacks(square,occupied),
+            Kind::QUEEN => attacks::queen_attacks(square,occupied),
+            Kind::KING => attacks::king_attacks(square),
+        }
+    }
+    pub fn perform_move_copy(&self, mv:Move) -> Self {
+        let mut new_state = self.clone();
+        new_state.perform_move_2(mv);
+        new_state
+    }
+    /// Plays `mv` and works out whether it ended the game.
+    pub fn perform_move_2(&mut self, mv:Move) {
+        self.apply_move(mv);
+        self.result = self.get_result();
+    }
+    // everything about playing a move except the game result, which needs every legal reply
+    fn apply_move(&mut self, mv:Move) {
+        let (from,to) = (mv.from_square(),mv.to_square());
+        self.hash ^= zobrist::move_key(&self.state,mv)
+            ^ zobrist::castling_key(&self.castling_rights)
+            ^ self.en_passant_key();
+        if let Some(ChessPiece{kind:Kind::KING,color}) = self.state.get(from) {
+            self.castling_rights.remove(color);
+        }
+        self.castling_rights.remove_for_square(from);
+        self.castling_rights.remove_for_square(to);
+        let pawn_moved = matches!(self.state.get(from),
+            Some(ChessPiece{kind:Kind::PAWN,..}));
+        if pawn_moved || mv.is_capture() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        move_pieces(&mut self.state,mv);
+        // the tile the pawn skipped over
+        self.en_passant = if mv.flags() == Move::DOUBLE_PAWN_PUSH {
+            Square::new(((from.index() + to.index()) / 2) as u8)
+        } else {
+            None
+        };
+        if self.player_turn == Color::BLACK {
+            self.fullmove_number += 1;
+        }
+        self.player_turn = self.player_turn.opposite();
+        // the side key is zero for white, so xoring black's flips whose turn it is
+        self.hash ^= zobrist::castling_key(&self.castling_rights)
+            ^ zobrist::side_key(Color::BLACK)
+            ^ self.en_passant_key();
+        debug_assert_eq!(self.hash, self.compute_hash(), "incremental hash is out of sync");
+        self.position_history.push(self.hash);
+    }
+    /// Plays `mv` so that `unmake_move` can take it back, without cloning the board.
+    /// Leaves `result` alone: a search or perft finds mates and stalemates itself
+    /// when there are no legal moves.
+    pub fn make_move(&mut self, mv:Move) {
+        self.undo_stack.push(Undo {
+            mv,
+            captured: self.captured_piece(mv),
+            castling_rights: self.castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+        });
+        self.apply_move(mv);
+    }
+    /// Takes back the last `make_move`.
+    pub fn unmake_move(&mut self) {
+        let undo = self.undo_stack.pop().expect("~no move to unmake");
+        let color = self.player_turn.opposite();
+        self.castling_rights = undo.castling_rights;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.hash = undo.hash;
+        unmove_pieces(&mut self.state,undo.mv,undo.captured);
+        if color == Color::BLACK {
+            self.fullmove_number -= 1;
+        }
+        self.player_turn = color;
+        self.position_history.pop();
+        debug_assert_eq!(self.hash, self.compute_hash(), "unmade hash is out of sync");
+    }
+    pub fn in_check(&self, color:Color) -> bool {
+        let king = self.find_piece(color,Kind::KING).expect("~king not found");
+        attackers_of(&self.state,king,color) != 0
+    }
+    fn get_result(&self) -> Option<GameResult> {
+        if self.get_moves_2(self.player_turn).is_empty() {
+            // checkmate and stalemate take precedence over the automatic draws
+            return if self.in_check(self.player_turn) {
+                Some(GameResult::win_for(self.player_turn.opposite()))
+            }
+            else {
+                Some(GameResult::Draw(DrawReason::Stalemate))
+            };
+        }
+        self.automatic_draw().map(GameResult::Draw)
+    }
+    /// A draw that ends the game by itself, whatever the players want.
+    pub fn automatic_draw(&self) -> Option<DrawReason> {
+        if self.halfmove_clock >= 150 {
+            Some(DrawReason::SeventyFiveMoveRule)
+        }
+        else if self.repetitions() >= 5 {
+            Some(DrawReason::FivefoldRepetition)
+        }
+        else if self.insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        }
+        else {
+            None
+        }
+    }
+    /// A draw the player to move may claim but which doesn't end the game on its own.
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.result.is_some() {
+            None
+        }
+        else if self.halfmove_clock >= 100 {
+            Some(DrawReason::FiftyMoveRule)
+        }
+        else if self.repetitions() >= 3 {
+            Some(DrawReason::ThreefoldRepetition)
+        }
+        else {
+            None
+        }
+    }
+    pub fn claim_draw(&mut self) -> bool {
+        match self.claimable_draw() {
+            Some(reason) => {
+                self.result = Some(GameResult::Draw(reason));
+                true
+            },
+            None => false,
+        }
+    }
+    /// How many times the current position has occurred, including now.
+    pub fn repetitions(&self) -> usize {
+        // a capture or pawn move can't be undone, so earlier positions can't repeat
+        let current = self.position_history.last().unwrap();
+        self.position_history.iter().rev()
+            .take(self.halfmove_clock as usize + 1)
+            .filter(|key| *key == current)
+            .count()
+    }
+    fn insufficient_material(&self) -> bool {
+        let both = |kind| self.state.pieces(Color::WHITE,kind) | self.state.pieces(Color::BLACK,kind);
+        if both(Kind::PAWN) | both(Kind::ROOK) | both(Kind::QUEEN) != 0 {
+            return false;
+        }
+        let bishops = both(Kind::BISHOP);
+        match (both(Kind::KNIGHT).count_ones(), bishops.count_ones()) {
+            (0,0) | (1,0) | (0,1) => true,
+            // bishops that all share one square color can never give mate
+            (0,_) => bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0,
+            _ => false,
+        }
+    }
+    pub fn turn_piece_selected(&self) -> bool {
+        self.selected_tile
+            .and_then(|square| self.get_piece(square))
+            .is_some_and(|piece| piece.color == self.player_turn)
+    }
+    fn find_piece(&self,color:Color,kind:Kind) -> Option<Square> {
+        squares(self.state.pieces(color,kind)).next()
+    }
+    /// Every legal move for the side to move.
+    pub fn legal_moves(&self) -> Vec::<Move> {
+        self.get_moves_2(self.player_turn)
+    }
+    pub fn get_moves_2(&self,player_color:Color) -> Vec::<Move> {
+        // pseudo-legal moves, then drop every move that leaves our own king attacked
+        let mut moves = Vec::<Move>::new();
+        let king = self.find_piece(player_color,Kind::KING).expect("~king not found");
+        for (piece,from) in self.get_player_pieces(player_color) {
+            self.add_piece_moves(piece,from,king,&mut moves);
+        }
+        moves
+    }
+    /// Whether `mv` is one of the legal moves, without generating the moves of every piece.
+    pub fn is_legal(&self, mv:Move) -> bool {
+        let piece = match self.get_piece(mv.from_square()) {
+            Some(piece) if piece.color == self.player_turn => piece,
+            _ => return false,
+        };
+        let king = self.find_piece(self.player_turn,Kind::KING).expect("~king not found");
+        let mut moves = Vec::<Move>::new();
+        self.add_piece_moves(piece,mv.from_square(),king,&mut moves);
+        moves.contains(&mv)
+    }
+    fn add_piece_moves(&self,piece:ChessPiece,from:Square,king:Square,moves:&mut Vec<Move>) {
+        let player_color = piece.color;
+        for (to,flags) in self.get_moveto_tiles(from,piece.kind,player_color) {
+            let mv = Move::new(from,to,flags);
+            if self.does_move_expose_king(mv,king,player_color) {
+                continue;
+            }
+            if piece.kind == Kind::PAWN && to.rank() == back_rank(&player_color.opposite()) {
+                for promotion in PROMOTION_KINDS {
+                    moves.push(Move::new(from,to,Move::promotion_flags(promotion) | flags));
+                }
+            }
+            else {
+                moves.push(mv);
+            }
+        }
+        if piece.kind == Kind::KING {
+            moves.append(&mut self.get_castling_moves(from,player_color));
+        }
+    }
+    fn get_castling_moves(&self,king:Square,color:Color) -> Vec<Move> {
+        let mut moves = Vec::<Move>::new();
+        let rank = back_rank(&color);
+        if king != tile(4,rank)
+            || !self.tile_under_attack(king,&color).is_empty() {
+            return moves;
+        }
+        if self.castling_rights.kingside(color)
+            && self.castling_path_clear(7,&[5,6],&[5,6],color) {
+            moves.push(Move::new(king,tile(6,rank),Move::KING_CASTLE));
+        }
+        if self.castling_rights.queenside(color)
+            && self.castling_path_clear(0,&[1,2,3],&[3,2],color) {
+            moves.push(Move::new(king,tile(2,rank),Move::QUEEN_CASTLE));
+        }
+        moves
+    }
+    fn castling_path_clear(&self,rookx:i32,between:&[i32],king_path:&[i32],color:Color) -> bool {
+        let rank = back_rank(&color);
+        let rook_present = self.get_piece(tile(rookx,rank)) == Some(ChessPiece::new(color,Kind::ROOK));
+        rook_present
+            && between.iter().all(|x| !self.tile_occupied(tile(*x,rank)))
+            && king_path.iter().all(|x| self.tile_under_attack(tile(*x,rank),&color).is_empty())
+    }
+    /// Enemy pieces (relative to `color`) that attack the tile.
+    pub fn tile_under_attack(&self,square:Square,color:&Color) -> Attackers {
+        tile_attackers(&self.state,square,*color)
+    }
+    fn does_move_expose_king(&self,mv:Move,king:Square,color:Color) -> bool {
+        // play the move on a scratch copy of the pieces, which covers pins along
+        // every line, checks that aren't answered and the en passant edge cases
+        let mut state = self.state;
+        move_pieces(&mut state,mv);
+        let king = if mv.from_square() == king { mv.to_square() } else { king };
+        attackers_of(&state,king,color) != 0
+    }
+    /// Whether `mv` leaves the opponent in check.
+    pub fn gives_check(&self, mv:Move) -> bool {
+        let mut state = self.state;
+        move_pieces(&mut state,mv);
+        let enemy_clr = self.player_turn.opposite();
+        let king = squares(state.pieces(enemy_clr,Kind::KING)).next().expect("~king not found");
+        attackers_of(&state,king,enemy_clr) != 0
+    }
+    /// The piece `mv` takes, including a pawn taken en passant.
+    pub fn captured_piece(&self, mv:Move) -> Option<ChessPiece> {
+        if mv.is_en_passant() {
+            return Some(ChessPiece::new(self.player_turn.opposite(),Kind::PAWN));
+        }
+        self.get_piece(mv.to_square())
+    }
+    pub fn tile_occupied(&self, square:Square) -> bool {
+        self.get_piece(square).is_some()
+    }
+    pub fn tile_occupied_by_enemy(&self,square:Square,color:&Color) -> bool {
+        self.state.color(color.opposite()) & square.bitboard() != 0
+    }
+    pub fn tile_occupied_by_ally(&self,square:Square,color:&Color) -> bool {
+        self.state.color(*color) & square.bitboard() != 0
+    }
+    // the tiles a piece can move to with the flags of each move, before promotions,
+    // castling and the check on our own king
+    fn get_moveto_tiles(&self,from:Square,kind:Kind,color:Color) -> Vec<(Square,u16)> {
+        let mut tiles = Vec::<(Square,u16)>::new();
+        match kind {
+            Kind::PAWN => {
+                let dir = y_direction(&color);
+                if let Some(push) = from.offset(0,dir).filter(|to| !self.tile_occupied(*to)) {
+                    tiles.push((push,Move::QUIET));
+                    if let Some(double) = push.offset(0,dir).filter(|to| !pawn_has_moved(from,&color) && !self.tile_occupied(*to)) {
+                        tiles.push((double,Move::DOUBLE_PAWN_PUSH));
+                    }
+                }
+                for dx in [1,-1] {
+                    if let Some(to) = from.offset(dx,dir).filter(|to| self.tile_occupied_by_enemy(*to,&color)) {
+                        tiles.push((to,Move::CAPTURE));
+                    }
+                }
+                if let Some(to) = self.en_passant {
+                    if color == self.player_turn && to.rank() == from.rank()+dir && (to.file() - from.file()).abs() == 1 {
+                        tiles.push((to,Move::EN_PASSANT));
+                    }
+                }
+            },
+            _ => {
+                let targets = self.attacks(ChessPiece::new(color,kind),from) & !self.state.color(color);
+                let enemy = self.state.color(color.opposite());
+                tiles.extend(squares(targets)
+                    .map(|to| (to, if enemy & to.bitboard() != 0 { Move::CAPTURE } else { Move::QUIET })));
+            },
+        }
+        tiles
+    }
+}
+fn tile_attackers(state:&State,square:Square,color:Color) -> Attackers {
+    squares(attackers_of(state,square,color))
+        .map(|from| (state.get(from).expect("~attacker tile is empty"),from))
+        .collect()
+}
+// the tiles of the enemy pieces (relative to `color`) attacking `square`
+fn attackers_of(state:&State,square:Square,color:Color) -> Bitboard {
+    let occupied = state.occupied();
+    let enemy = |kind| state.pieces(color.opposite(),kind);
+    let rooks = enemy(Kind::ROOK) | enemy(Kind::QUEEN);
+    let bishops = enemy(Kind::BISHOP) | enemy(Kind::QUEEN);
+    // enemy pawns hit us from where our own pawn would capture
+    (attacks::knight_attacks(square) & enemy(Kind::KNIGHT))
+        | (attacks::king_attacks(square) & enemy(Kind::KING))
+        | (attacks::pawn_attacks(color,square) & enemy(Kind::PAWN))
+        | (attacks::rook_attacks(square,occupied) & rooks)
+        | (attacks::bishop_attacks(square,occupied) & bishops)
+}
+// where the rook starts and ends when `mv` castles
+fn castling_rook(mv:Move) -> (Square,Square) {
+    let rank = mv.from_square().rank();
+    if mv.flags() == Move::KING_CASTLE { (tile(7,rank),tile(5,rank)) } else { (tile(0,rank),tile(3,rank)) }
+}
+// the pawn taken en passant sits beside the capturing one, not on the target tile
+fn en_passant_victim(mv:Move) -> Square {
+    tile(mv.to_square().file(),mv.from_square().rank())
+}
+// moves the pieces only, the rest of the board bookkeeping is up to the caller
+fn move_pieces(state:&mut State,mv:Move) {
+    let (from,to) = (mv.from_square(),mv.to_square());
+    let piece = state.get(from).expect("~no piece to move");
+    if mv.is_castle() {
+        // the rook jumps to the other side of the king
+        let (rook_from,rook_to) = castling_rook(mv);
+        state.set(rook_to,state.get(rook_from));
+        state.set(rook_from,None);
+    }
+    else if mv.is_en_passant() {
+        state.set(en_passant_victim(mv),None);
+    }
+    state.set(to,match mv.promotion() {
+        Some(kind) => Some(ChessPiece::new(piece.color, kind)),
+        None => Some(piece),
+    });
+    state.set(from,None);
+}
+// the reverse of move_pieces, putting back whatever was captured
+fn unmove_pieces(state:&mut State,mv:Move,captured:Option<ChessPiece>) {
+    let (from,to) = (mv.from_square(),mv.to_square());
+    let piece = state.get(to).expect("~no piece to move back");
+    let piece = if mv.is_promotion() { ChessPiece::new(piece.color, Kind::PAWN) } else { piece };
+    if mv.is_castle() {
+        let (rook_from,rook_to) = castling_rook(mv);
+        state.set(rook_from,state.get(rook_to));
+        state.set(rook_to,None);
+    }
+    if mv.is_en_passant() {
+        state.set(to,None);
+        state.set(en_passant_victim(mv),captured);
+    }
+    else {
+        state.set(to,captured);
+    }
+    state.set(from,Some(piece));
+}
+fn y_direction(color: &Color) -> i32 { // returns 1 or -1
+    match color {
+        Color::BLACK => -1,
+        Color::WHITE => 1,
+    }
+}
+fn back_rank(color: &Color) -> i32 {
+    match color {
+        Color::BLACK => 7,
+        Color::WHITE => 0,
+    }
+}
+fn pawn_has_moved(square:Square, color: &Color) -> bool {
+    match color {
+        Color::BLACK => square.rank() != 6,
+        Color::WHITE => square.rank() != 1,
+    }
+}
+// for coordinates that are known to be on the board
+fn tile(x:i32, y:i32) -> Square {
+    Square::from_coords(x,y).expect("~tile off the board")
+}
+fn get_initial_state() -> State {
+    let mut state = State::default();
+    let back_row = [Kind::ROOK, Kind::KNIGHT, Kind::BISHOP, Kind::QUEEN, Kind::KING, Kind::BISHOP, Kind::KNIGHT, Kind::ROOK];
+    for (x,kind) in back_row.into_iter().enumerate() {
+        let x = x as i32;
+        state.set(tile(x,0),Some(ChessPiece::new(Color::WHITE, kind)));
+        state.set(tile(x,1),Some(ChessPiece::new(Color::WHITE, Kind::PAWN)));
+        state.set(tile(x,7),Some(ChessPiece::new(Color::BLACK, kind)));
+        state.set(tile(x,6),Some(ChessPiece::new(Color::BLACK, Kind::PAWN)));
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::notation;
+
+    fn play(fen: &str, sans: &[&str]) -> Board {
+        let mut board = Board::from_fen(fen).unwrap();
+        for san in sans {
+            let ply = notation::parse_san(&board, san).unwrap_or_else(|| panic!("{} is not legal", san));
+            board.perform_move_2(ply);
+        }
+        board
+    }
+    fn draw(reason: DrawReason) -> Option<GameResult> {
+        Some(GameResult::Draw(reason))
+    }
+
+    const ROOKS: &str = "r3k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+
+    #[test]
+    fn piece_attacks() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/1P6/R3K3 w - - 0 1").unwrap();
+        let attacked = |kind, square: &str| {
+            let square: Square = square.parse().unwrap();
+            let mut names: Vec<String> = Square::all()
+                .filter(|tile| board.attacks(ChessPiece::new(Color::WHITE, kind), square) & tile.bitboard() != 0)
+                .map(|tile| tile.to_string())
+                .collect();
+            names.sort();
+            names.join(" ")
+        };
+        // stops at the first piece either way, own pieces included
+        assert_eq!(attacked(Kind::ROOK, "a1"), "a2 a3 a4 a5 a6 a7 a8 b1 c1 d1 e1");
+        assert_eq!(attacked(Kind::BISHOP, "a1"), "b2");
+        assert_eq!(attacked(Kind::KNIGHT, "a1"), "b3 c2");
+        assert_eq!(attacked(Kind::PAWN, "b2"), "a3 c3");
+        assert_eq!(attacked(Kind::KING, "e1"), "d1 d2 e2 f1 f2");
+    }
+
+    #[test]
+    fn fifty_move_rule() {
+        let board = play("r3k3/8/8/8/8/8/P7/R3K3 w - - 97 80", &["Rb1", "Rb8"]);
+        assert_eq!((board.halfmove_clock, board.claimable_draw(), board.result), (99, None, None));
+        let mut board = play("r3k3/8/8/8/8/8/P7/R3K3 w - - 97 80", &["Rb1", "Rb8", "Rc1"]);
+        assert_eq!((board.claimable_draw(), board.automatic_draw(), board.result), (Some(DrawReason::FiftyMoveRule), None, None));
+        assert!(board.claim_draw());
+        assert_eq!(board.result, draw(DrawReason::FiftyMoveRule));
+        // a pawn move starts the count again
+        let board = play("r3k3/8/8/8/8/8/P7/R3K3 w - - 97 80", &["Rb1", "Rb8", "a3"]);
+        assert_eq!((board.halfmove_clock, board.claimable_draw()), (0, None));
+    }
+
+    #[test]
+    fn seventy_five_move_rule() {
+        let board = play("r3k3/8/8/8/8/8/P7/R3K3 w - - 148 100", &["Rb1"]);
+        assert_eq!((board.automatic_draw(), board.result), (None, None));
+        let board = play("r3k3/8/8/8/8/8/P7/R3K3 w - - 148 100", &["Rb1", "Rb8"]);
+        assert_eq!(board.automatic_draw(), Some(DrawReason::SeventyFiveMoveRule));
+        assert_eq!(board.result, draw(DrawReason::SeventyFiveMoveRule));
+        // checkmate on the last move still counts
+        let board = play("6k1/5ppp/8/8/8/8/8/R5K1 w - - 149 100", &["Ra8#"]);
+        assert_eq!(board.result, Some(GameResult::WhiteWins));
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let shuffle = ["Ra2", "Ra7", "Ra1", "Ra8"];
+        let board = play(ROOKS, &shuffle);
+        assert_eq!((board.repetitions(), board.claimable_draw()), (2, None));
+        let mut board = play(ROOKS, &shuffle.repeat(2));
+        assert_eq!(board.repetitions(), 3);
+        assert_eq!((board.claimable_draw(), board.automatic_draw(), board.result), (Some(DrawReason::ThreefoldRepetition), None, None));
+        assert!(board.claim_draw());
+        assert_eq!(board.result, draw(DrawReason::ThreefoldRepetition));
+        assert!(!board.claim_draw());
+        // losing the right to castle makes it a different position
+        let board = play("r3k3/8/8/8/8/8/8/R3K3 w Qq - 0 1", &shuffle.repeat(2));
+        assert_eq!((board.repetitions(), board.claimable_draw()), (2, None));
+    }
+
+    #[test]
+    fn fivefold_repetition() {
+        let shuffle = ["Ra2", "Ra7", "Ra1", "Ra8"];
+        let board = play(ROOKS, &shuffle.repeat(3));
+        assert_eq!((board.repetitions(), board.result), (4, None));
+        let board = play(ROOKS, &shuffle.repeat(4));
+        assert_eq!(board.repetitions(), 5);
+        assert_eq!(board.result, draw(DrawReason::FivefoldRepetition));
+        // a capture clears the history that can repeat
+        let board = play("r3k3/8/8/8/8/8/p7/R3K3 w - - 0 1", &["Rxa2", "Ra7", "Ra1", "Ra8"]);
+        assert_eq!(board.repetitions(), 1);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let automatic = |fen| Board::from_fen(fen).unwrap().automatic_draw();
+        let insufficient = Some(DrawReason::InsufficientMaterial);
+        assert_eq!(automatic("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), insufficient);
+        assert_eq!(automatic("4k3/8/8/8/8/8/8/4KN2 w - - 0 1"), insufficient);
+        assert_eq!(automatic("4k3/8/8/8/8/8/8/4KB2 w - - 0 1"), insufficient);
+        // c1 and f8 are both dark squares, c1 and c8 are not
+        assert_eq!(automatic("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1"), insufficient);
+        assert_eq!(automatic("4k3/8/8/8/8/8/8/2BBK3 w - - 0 1"), None);
+        assert_eq!(automatic("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1"), None);
+        assert_eq!(automatic("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1"), None);
+        assert_eq!(automatic("4kn2/8/8/8/8/8/8/2B1K3 w - - 0 1"), None);
+        assert_eq!(automatic("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"), None);
+        assert_eq!(automatic("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), None);
+        // taking the last pawn ends the game
+        let board = play("4k3/8/8/8/8/8/3p4/2B1K3 w - - 0 1", &["Kxd2"]);
+        assert_eq!(board.result, draw(DrawReason::InsufficientMaterial));
+    }
+}
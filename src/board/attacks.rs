@@ -0,0 +1,224 @@
+// Attack sets as bitboards. Knights, kings and pawns come from tables built at
+// compile time; rooks and bishops use magic bitboards, whose tables are filled
+// from fixed magic numbers the first time they are needed.
+use super::bitboard::{Bitboard,square};
+use super::{BISHOP_DIRECTIONS,KING_OFFSETS,KNIGHT_OFFSETS,ROOK_DIRECTIONS};
+use super::super::pieces::Color;
+use super::super::square::Square;
+use std::sync::OnceLock;
+
+const fn offset_table(offsets: &[(i32,i32)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut index = 0;
+    while index < 64 {
+        let (x,y) = ((index % 8) as i32, (index / 8) as i32);
+        let mut i = 0;
+        while i < offsets.len() {
+            let (tx,ty) = (x + offsets[i].0, y + offsets[i].1);
+            if tx >= 0 && tx < 8 && ty >= 0 && ty < 8 {
+                table[index] |= 1 << square(tx,ty);
+            }
+            i += 1;
+        }
+        index += 1;
+    }
+    table
+}
+
+const KNIGHT_ATTACKS: [Bitboard; 64] = offset_table(&KNIGHT_OFFSETS);
+const KING_ATTACKS: [Bitboard; 64] = offset_table(&KING_OFFSETS);
+const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [offset_table(&[(-1,1),(1,1)]), offset_table(&[(-1,-1),(1,-1)])];
+
+pub fn knight_attacks(from:Square) -> Bitboard {
+    KNIGHT_ATTACKS[from.index()]
+}
+pub fn king_attacks(from:Square) -> Bitboard {
+    KING_ATTACKS[from.index()]
+}
+/// The tiles a `color` pawn on `from` attacks.
+pub fn pawn_attacks(color:Color, from:Square) -> Bitboard {
+    PAWN_ATTACKS[color.index()][from.index()]
+}
+pub fn rook_attacks(from:Square, occupied:Bitboard) -> Bitboard {
+    magics().rook[from.index()].attacks(occupied, &magics().table)
+}
+pub fn bishop_attacks(from:Square, occupied:Bitboard) -> Bitboard {
+    magics().bishop[from.index()].attacks(occupied, &magics().table)
+}
+pub fn queen_attacks(from:Square, occupied:Bitboard) -> Bitboard {
+    rook_attacks(from,occupied) | bishop_attacks(from,occupied)
+}
+
+// Everything a slider on (x,y) sees along `directions`, stopping at (and
+// including) the first occupied tile. Slow, but it is what the magics are built from.
+fn ray_attacks(x:i32, y:i32, occupied:Bitboard, directions:&[(i32,i32)]) -> Bitboard {
+    let mut attacks = 0;
+    for (dx,dy) in directions {
+        let (mut tx,mut ty) = (x + dx, y + dy);
+        while (0..8).contains(&tx) && (0..8).contains(&ty) {
+            let bit = 1 << square(tx,ty);
+            attacks |= bit;
+            if occupied & bit != 0 {
+                break;
+            }
+            tx += dx;
+            ty += dy;
+        }
+    }
+    attacks
+}
+
+// the tiles whose occupancy changes the attacks: the rays without their last tile
+fn relevant_mask(x:i32, y:i32, directions:&[(i32,i32)]) -> Bitboard {
+    let mut mask = 0;
+    for (dx,dy) in directions {
+        let (mut tx,mut ty) = (x + dx, y + dy);
+        while (0..8).contains(&(tx + dx)) && (0..8).contains(&(ty + dy)) {
+            mask |= 1 << square(tx,ty);
+            tx += dx;
+            ty += dy;
+        }
+    }
+    mask
+}
+
+#[derive(Clone,Copy,Default)]
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+impl Magic {
+    fn index(&self, occupied:Bitboard) -> usize {
+        ((occupied & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+    fn attacks(&self, occupied:Bitboard, table:&[Bitboard]) -> Bitboard {
+        table[self.offset + self.index(occupied)]
+    }
+}
+
+struct Magics {
+    rook: [Magic; 64],
+    bishop: [Magic; 64],
+    table: Vec<Bitboard>,
+}
+
+// Found once by trying random sparse numbers; each maps every relevant occupancy
+// of its tile to a slot without clashing with a different attack set.
+const ROOK_MAGICS: [u64; 64] = [
+    0x08800090A0844000, 0x0140004020001000, 0x0600082200108040, 0x1080248008005000,
+    0x4200110200080420, 0x0580018024000A00, 0x81004081000C1200, 0x21000A0388486300,
+    0x121280104000A080, 0x8A40400020100048, 0xA001001100412000, 0x0082001042002049,
+    0x4191800800802400, 0x0002000802003004, 0x0400800100802200, 0x100100110020C882,
+    0x08C3020020844201, 0x009000C000200240, 0x2022220040108202, 0x0081010010012208,
+    0xCA08008028040080, 0x110081800C000200, 0x000480804B002200, 0x0010120001418114,
+    0x010092208004C000, 0x1881200040005009, 0x0020809200220044, 0x1188100080080084,
+    0x0018004D00110008, 0x0090C00801042030, 0x9080481400015002, 0x002004020012A041,
+    0x0082408202002100, 0x4320004020401000, 0x3800312082004201, 0x0080802800801002,
+    0x0102801800800400, 0x008A804200800400, 0x00401801D4001002, 0x008A0288C2000401,
+    0x8820804013208001, 0x8080D00220024000, 0x0008410020010011, 0x000200109B420020,
+    0x1008000451010008, 0x4640040002008080, 0x1000080106040070, 0x20000400A0420001,
+    0x4000420108288200, 0xC910004000200840, 0x00504020030A1100, 0x0001080210008080,
+    0x0111240048008080, 0x200A007048044200, 0x4080100201A85400, 0x040900108200C500,
+    0x2002182104408001, 0x0040208410430202, 0x0032012010084282, 0x2807003000090421,
+    0x82920060C8041042, 0x01C1002804004201, 0x04000A00B1102804, 0x0420040700628046,
+];
+const BISHOP_MAGICS: [u64; 64] = [
+    0x0318010104010201, 0x1108500080830100, 0x0090090845018000, 0x0014040182401802,
+    0x4043104004001602, 0x4000900420110410, 0x8002111012900C80, 0x460084004104200C,
+    0x000140080A008211, 0x0000040108010900, 0x801410013A006010, 0x0000040400800040,
+    0x0800040520020A00, 0x0090098804420200, 0x0800010801100A00, 0x4802808048021000,
+    0x0248000410100240, 0x9048448210490208, 0x2012288C00220200, 0x0943060804110000,
+    0x0004040202110280, 0x1011200110101003, 0x1808800211900900, 0x400142408E080100,
+    0x1010040010218208, 0x02010C0020643400, 0x012808030B020228, 0x0008080004820003,
+    0x0000840010822004, 0x801800400080E010, 0x0C31040581040100, 0x0040C10002028202,
+    0x4618824003100440, 0x4408011001088200, 0x0800441005120020, 0x2000020080C80080,
+    0x2020188400A08020, 0x0010018202002200, 0x0014088080022800, 0x0000810300020490,
+    0x408C0202110140A0, 0x0002080184110820, 0x5086108413011000, 0x0030002214000800,
+    0x8C00200204100280, 0x0002081001000260, 0x80080AC804401200, 0x0404030C00201100,
+    0x2400C804102B0084, 0x080300C104210052, 0x00004080E8080100, 0x1008105046080200,
+    0x802E00900A120022, 0x2209212410208000, 0x061030020820C000, 0x00100C3840802404,
+    0x1044420820880400, 0x4040010246100402, 0x0000500040441000, 0x0000008010208808,
+    0x0801800110120880, 0x0008012024031208, 0x0400200510020044, 0x8804041004010530,
+];
+
+fn magics() -> &'static Magics {
+    static MAGICS: OnceLock<Magics> = OnceLock::new();
+    MAGICS.get_or_init(|| {
+        let mut table = Vec::<Bitboard>::new();
+        let rook = std::array::from_fn(|index| fill_magic(index, ROOK_MAGICS[index], &ROOK_DIRECTIONS, &mut table));
+        let bishop = std::array::from_fn(|index| fill_magic(index, BISHOP_MAGICS[index], &BISHOP_DIRECTIONS, &mut table));
+        Magics { rook, bishop, table }
+    })
+}
+
+/// Builds the slider tables now rather than on the first lookup.
+pub fn init() {
+    magics();
+}
+
+// stores the attacks for every subset of the relevant mask, by the carry-rippler trick
+fn fill_magic(index:usize, magic:u64, directions:&[(i32,i32)], table:&mut Vec<Bitboard>) -> Magic {
+    let (x,y) = ((index % 8) as i32, (index / 8) as i32);
+    let mask = relevant_mask(x, y, directions);
+    let bits = mask.count_ones();
+    let magic = Magic { mask, magic, shift: 64 - bits, offset: table.len() };
+    table.resize(table.len() + (1 << bits), 0);
+    let mut subset: Bitboard = 0;
+    loop {
+        table[magic.offset + magic.index(subset)] = ray_attacks(x, y, subset, directions);
+        subset = subset.wrapping_sub(mask) & mask;
+        if subset == 0 {
+            break;
+        }
+    }
+    magic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next_random(state: &mut u64) -> u64 {
+        // xorshift64
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    fn offsets_walk(x:i32, y:i32, offsets:&[(i32,i32)]) -> Bitboard {
+        offsets.iter()
+            .map(|(dx,dy)| (x + dx, y + dy))
+            .filter(|(tx,ty)| (0..8).contains(tx) && (0..8).contains(ty))
+            .fold(0, |attacks,(tx,ty)| attacks | 1 << square(tx,ty))
+    }
+
+    #[test]
+    fn leaper_tables_match_offsets() {
+        for from in Square::all() {
+            let (x,y) = from.coords();
+            assert_eq!(knight_attacks(from), offsets_walk(x,y,&KNIGHT_OFFSETS));
+            assert_eq!(king_attacks(from), offsets_walk(x,y,&KING_OFFSETS));
+            assert_eq!(pawn_attacks(Color::WHITE,from), offsets_walk(x,y,&[(-1,1),(1,1)]));
+            assert_eq!(pawn_attacks(Color::BLACK,from), offsets_walk(x,y,&[(-1,-1),(1,-1)]));
+        }
+    }
+
+    #[test]
+    fn magic_attacks_match_ray_walking() {
+        let mut rng = 0x9E37_79B9_7F4A_7C15_u64;
+        for from in Square::all() {
+            let (x,y) = from.coords();
+            for _ in 0..200 {
+                // sparse and dense boards both
+                let occupied = next_random(&mut rng) & next_random(&mut rng) | (next_random(&mut rng) & 0xFF);
+                assert_eq!(rook_attacks(from,occupied), ray_attacks(x,y,occupied,&ROOK_DIRECTIONS));
+                assert_eq!(bishop_attacks(from,occupied), ray_attacks(x,y,occupied,&BISHOP_DIRECTIONS));
+                assert_eq!(queen_attacks(from,occupied),
+                           ray_attacks(x,y,occupied,&ROOK_DIRECTIONS) | ray_attacks(x,y,occupied,&BISHOP_DIRECTIONS));
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::super::pieces::{ChessPiece,Color,Kind};
+use super::super::square::Square;
+
+/// One bit per tile, bit `y * 8 + x` for the tile at (x,y), so a1 is bit 0 and h8 bit 63.
+pub type Bitboard = u64;
+
+pub const fn square(x:i32, y:i32) -> usize {
+    (y * 8 + x) as usize
+}
+
+/// The square of every set bit, lowest first.
+pub fn squares(mut bitboard: Bitboard) -> impl Iterator<Item = Square> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let index = bitboard.trailing_zeros() as u8;
+        bitboard &= bitboard - 1;
+        Square::new(index)
+    })
+}
+
+// Where the pieces are, kept twice over: a bitboard per color and kind for
+// set-wise questions, and a mailbox for asking what stands on one tile.
+#[derive(Clone,Copy,PartialEq,Eq)]
+pub(super) struct State {
+    pub(super) mailbox: [Option<ChessPiece>; 64],
+    pieces: [[Bitboard; 6]; 2],
+    colors: [Bitboard; 2],
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            mailbox: [None; 64],
+            pieces: [[0; 6]; 2],
+            colors: [0; 2],
+        }
+    }
+}
+
+impl State {
+    pub(super) fn get(&self, square:Square) -> Option<ChessPiece> {
+        self.mailbox[square.index()]
+    }
+    pub(super) fn set(&mut self, square:Square, piece:Option<ChessPiece>) {
+        let index = square.index();
+        let bit = square.bitboard();
+        if let Some(old) = self.mailbox[index] {
+            self.pieces[old.color.index()][old.kind.index()] &= !bit;
+            self.colors[old.color.index()] &= !bit;
+        }
+        if let Some(new) = piece {
+            self.pieces[new.color.index()][new.kind.index()] |= bit;
+            self.colors[new.color.index()] |= bit;
+        }
+        self.mailbox[index] = piece;
+    }
+    pub(super) fn pieces(&self, color:Color, kind:Kind) -> Bitboard {
+        self.pieces[color.index()][kind.index()]
+    }
+    pub(super) fn color(&self, color:Color) -> Bitboard {
+        self.colors[color.index()]
+    }
+    pub(super) fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+}
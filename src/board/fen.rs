@@ -0,0 +1,318 @@
+use super::{Board,CastlingRights,State,back_rank,tile};
+use super::super::pieces::{ChessPiece,Color,Kind};
+use super::super::square::Square;
+use std::fmt;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum FenError {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    WrongRankLength { rank: usize, length: usize },
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    KingCount { color: Color, count: usize },
+    PawnOnBackRank(String),
+    OpponentInCheck,
+}
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::WrongFieldCount(count) =>
+                write!(f, "expected 4 or 6 space separated fields, found {}", count),
+            FenError::WrongRankCount(count) =>
+                write!(f, "expected 8 ranks in the piece placement, found {}", count),
+            FenError::WrongRankLength{rank,length} =>
+                write!(f, "rank {} describes {} squares instead of 8", rank, length),
+            FenError::InvalidPiece(c) => write!(f, "'{}' is not a piece", c),
+            FenError::InvalidSideToMove(s) => write!(f, "side to move should be w or b, found '{}'", s),
+            FenError::InvalidCastling(s) => write!(f, "invalid castling rights '{}'", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en passant square '{}'", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            FenError::InvalidFullmoveNumber(s) => write!(f, "invalid fullmove number '{}'", s),
+            FenError::KingCount{color,count} =>
+                write!(f, "{:?} should have exactly one king, found {}", color, count),
+            FenError::PawnOnBackRank(tile) => write!(f, "pawn on the back rank at {}", tile),
+            FenError::OpponentInCheck => write!(f, "the side that just moved is in check"),
+        }
+    }
+}
+impl std::error::Error for FenError {}
+
+impl Board {
+    /// Reads a position in Forsyth-Edwards Notation. The move counters may be left off.
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(FenError::WrongFieldCount(fields.len()));
+        }
+        let mut board = Board::default();
+        board.state = parse_placement(fields[0])?;
+        board.player_turn = match fields[1] {
+            "w" => Color::WHITE,
+            "b" => Color::BLACK,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+        board.castling_rights = parse_castling(fields[2], &board.state)?;
+        board.en_passant = parse_en_passant(fields[3], &board)?;
+        if fields.len() == 6 {
+            board.halfmove_clock = fields[4].parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+            board.fullmove_number = match fields[5].parse() {
+                Ok(number) if number > 0 => number,
+                _ => return Err(FenError::InvalidFullmoveNumber(fields[5].to_string())),
+            };
+        }
+        for color in [Color::WHITE, Color::BLACK] {
+            let count = board.get_pieces().iter()
+                .filter(|(piece,_)| *piece == ChessPiece::new(color,Kind::KING))
+                .count();
+            if count != 1 {
+                return Err(FenError::KingCount{color,count});
+            }
+        }
+        if board.in_check(board.player_turn.opposite()) {
+            return Err(FenError::OpponentInCheck);
+        }
+        board.hash = board.compute_hash();
+        board.position_history = vec![board.hash];
+        board.result = board.get_result();
+        Ok(board)
+    }
+    pub fn to_fen(&self) -> String {
+        let mut ranks = Vec::<String>::new();
+        for y in (0..=7).rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for x in 0..=7 {
+                match self.get_piece(tile(x,y)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        rank.push(piece_char(&piece));
+                    },
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            ranks.push(rank);
+        }
+        let side = match self.player_turn {
+            Color::WHITE => "w",
+            Color::BLACK => "b",
+        };
+        let rights = self.castling_rights;
+        let mut castling: String = [
+            (rights.white_kingside,'K'),
+            (rights.white_queenside,'Q'),
+            (rights.black_kingside,'k'),
+            (rights.black_queenside,'q'),
+        ].iter().filter(|(allowed,_)| *allowed).map(|(_,c)| *c).collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let en_passant = match self.en_passant {
+            Some(square) => square.to_string(),
+            None => "-".to_string(),
+        };
+        format!("{} {} {} {} {} {}", ranks.join("/"), side, castling, en_passant,
+                self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+pub fn piece_char(piece: &ChessPiece) -> char {
+    let c = match piece.kind {
+        Kind::PAWN => 'p',
+        Kind::KNIGHT => 'n',
+        Kind::BISHOP => 'b',
+        Kind::ROOK => 'r',
+        Kind::QUEEN => 'q',
+        Kind::KING => 'k',
+    };
+    match piece.color {
+        Color::WHITE => c.to_ascii_uppercase(),
+        Color::BLACK => c,
+    }
+}
+fn parse_piece(c: char) -> Option<ChessPiece> {
+    let kind = match c.to_ascii_lowercase() {
+        'p' => Kind::PAWN,
+        'n' => Kind::KNIGHT,
+        'b' => Kind::BISHOP,
+        'r' => Kind::ROOK,
+        'q' => Kind::QUEEN,
+        'k' => Kind::KING,
+        _ => return None,
+    };
+    let color = if c.is_ascii_uppercase() { Color::WHITE } else { Color::BLACK };
+    Some(ChessPiece::new(color,kind))
+}
+fn parse_placement(placement: &str) -> Result<State, FenError> {
+    let mut state = State::default();
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::WrongRankCount(ranks.len()));
+    }
+    for (row,rank) in ranks.iter().enumerate() {
+        // FEN lists the eighth rank first
+        let y = 7 - row;
+        let mut x = 0;
+        for c in rank.chars() {
+            if let Some(skip) = c.to_digit(10).filter(|skip| (1..=8).contains(skip)) {
+                x += skip as usize;
+                continue;
+            }
+            let piece = parse_piece(c).ok_or(FenError::InvalidPiece(c))?;
+            if x < 8 {
+                if piece.kind == Kind::PAWN && (y == 0 || y == 7) {
+                    return Err(FenError::PawnOnBackRank(tile(x as i32,y as i32).to_string()));
+                }
+                state.set(tile(x as i32,y as i32),Some(piece));
+            }
+            x += 1;
+        }
+        if x != 8 {
+            return Err(FenError::WrongRankLength{rank: y+1, length: x});
+        }
+    }
+    Ok(state)
+}
+fn parse_castling(castling: &str, state: &State) -> Result<CastlingRights, FenError> {
+    let invalid = || FenError::InvalidCastling(castling.to_string());
+    let mut rights = CastlingRights {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+    if castling == "-" {
+        return Ok(rights);
+    }
+    for c in castling.chars() {
+        let (color,rookx,right) = match c {
+            'K' => (Color::WHITE, 7, &mut rights.white_kingside),
+            'Q' => (Color::WHITE, 0, &mut rights.white_queenside),
+            'k' => (Color::BLACK, 7, &mut rights.black_kingside),
+            'q' => (Color::BLACK, 0, &mut rights.black_queenside),
+            _ => return Err(invalid()),
+        };
+        // the king and rook have to still be on their starting tiles
+        let rank = back_rank(&color);
+        if *right
+            || state.get(tile(4,rank)) != Some(ChessPiece::new(color,Kind::KING))
+            || state.get(tile(rookx,rank)) != Some(ChessPiece::new(color,Kind::ROOK)) {
+            return Err(invalid());
+        }
+        *right = true;
+    }
+    Ok(rights)
+}
+fn parse_en_passant(en_passant: &str, board: &Board) -> Result<Option<Square>, FenError> {
+    if en_passant == "-" {
+        return Ok(None);
+    }
+    let invalid = || FenError::InvalidEnPassant(en_passant.to_string());
+    let square: Square = en_passant.parse().map_err(|_| invalid())?;
+    // the pawn that just made a double step has to be right in front of the tile,
+    // with the tile it started from left empty
+    let (expected_y,pawn_y,start_y,pawn_color) = match board.player_turn {
+        Color::WHITE => (5, 4, 6, Color::BLACK),
+        Color::BLACK => (2, 3, 1, Color::WHITE),
+    };
+    let on_file = |y| tile(square.file(),y);
+    if square.rank() != expected_y
+        || board.tile_occupied(square)
+        || board.tile_occupied(on_file(start_y))
+        || board.get_piece(on_file(pawn_y)) != Some(ChessPiece::new(pawn_color,Kind::PAWN)) {
+        return Err(invalid());
+    }
+    Ok(Some(square))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::START_FEN;
+
+    fn error(fen: &str) -> FenError {
+        Board::from_fen(fen).err().expect("FEN should be rejected")
+    }
+
+    #[test]
+    fn round_trips() {
+        let fens = [
+            START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b Kq d3 0 3",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 13 58",
+        ];
+        for fen in fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+        // the move counters may be left off
+        let board = Board::from_fen("8/8/8/8/8/8/8/K1k5 b - -").unwrap();
+        assert_eq!(board.to_fen(), "8/8/8/8/8/8/8/K1k5 b - - 0 1");
+        assert_eq!(Board::default().to_fen(), START_FEN);
+    }
+
+    #[test]
+    fn rejects_bad_fields() {
+        assert_eq!(error("8/8/8/8/8/8/8/K1k5 w -"), FenError::WrongFieldCount(3));
+        assert_eq!(error("8/8/8/8/8/8/8/K1k5 w - - 0"), FenError::WrongFieldCount(5));
+        assert_eq!(error("8/8/8/8/8/8/K1k5 w - - 0 1"), FenError::WrongRankCount(7));
+        assert_eq!(error("8/8/8/8/8/8/8/K1k4 w - - 0 1"), FenError::WrongRankLength{rank: 1, length: 7});
+        assert_eq!(error("8/8/8/8/8/8/8/K1k6 w - - 0 1"), FenError::WrongRankLength{rank: 1, length: 9});
+        assert_eq!(error("8/8/8/8/8/8/8/K1x5 w - - 0 1"), FenError::InvalidPiece('x'));
+        assert_eq!(error("8/8/8/8/8/8/8/K1k5 x - - 0 1"), FenError::InvalidSideToMove("x".to_string()));
+        assert_eq!(error("8/8/8/8/8/8/8/K1k5 w - - x 1"), FenError::InvalidHalfmoveClock("x".to_string()));
+        assert_eq!(error("8/8/8/8/8/8/8/K1k5 w - - 0 0"), FenError::InvalidFullmoveNumber("0".to_string()));
+    }
+
+    #[test]
+    fn rejects_bad_castling() {
+        let bad = |castling: &str| FenError::InvalidCastling(castling.to_string());
+        assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K2R w KX - 0 1"), bad("KX"));
+        assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K2R w KK - 0 1"), bad("KK"));
+        // the rook or king has moved off its starting tile
+        assert_eq!(error("r3k2r/8/8/8/8/8/8/R3K1R1 w K - 0 1"), bad("K"));
+        assert_eq!(error("r3k2r/8/8/8/8/8/8/R4K1R w Q - 0 1"), bad("Q"));
+        assert_eq!(error("r3k3/8/8/8/8/8/8/R3K2R w k - 0 1"), bad("k"));
+    }
+
+    #[test]
+    fn rejects_bad_en_passant() {
+        let bad = |square: &str| FenError::InvalidEnPassant(square.to_string());
+        assert_eq!(error("4k3/8/8/3pP3/8/8/8/4K3 w - z9 0 1"), bad("z9"));
+        // the tile has to be behind a pawn that just moved two steps
+        assert_eq!(error("4k3/8/8/3pP3/8/8/8/4K3 w - d3 0 1"), bad("d3"));
+        assert_eq!(error("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1"), bad("d6"));
+        assert_eq!(error("4k3/8/3n4/3pP3/8/8/8/4K3 w - d6 0 1"), bad("d6"));
+        // and the pawn's starting tile has to be empty
+        assert_eq!(error("4k3/3n4/8/3pP3/8/8/8/4K3 w - d6 0 1"), bad("d6"));
+        assert!(Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").is_ok());
+    }
+
+    #[test]
+    fn rejects_impossible_positions() {
+        assert_eq!(error("8/8/8/8/8/8/8/K7 w - - 0 1"), FenError::KingCount{color: Color::BLACK, count: 0});
+        assert_eq!(error("k7/8/8/8/8/8/8/KK6 w - - 0 1"), FenError::KingCount{color: Color::WHITE, count: 2});
+        assert_eq!(error("k7/8/8/8/8/8/8/K6P w - - 0 1"), FenError::PawnOnBackRank("h1".to_string()));
+        assert_eq!(error("k7/8/8/8/8/8/8/R6K w - - 0 1"), FenError::OpponentInCheck);
+    }
+
+    #[test]
+    fn errors_describe_the_problem() {
+        assert_eq!(error("8/8/8/8/8/8/8/K1k5 x - - 0 1").to_string(), "side to move should be w or b, found 'x'");
+        assert_eq!(error("k7/8/8/8/8/8/8/R6K w - - 0 1").to_string(), "the side that just moved is in check");
+    }
+}
@@ -0,0 +1,127 @@
+use super::Board;
+use super::super::moves::Move;
+
+impl Board {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep.
+    pub fn perft(&self, depth:u32) -> u64 {
+        self.clone().perft_in_place(depth)
+    }
+    /// Perft split up by the first move, handy for tracking down a wrong count.
+    pub fn divide(&self, depth:u32) -> Vec<(Move,u64)> {
+        let mut board = self.clone();
+        self.get_moves_2(self.player_turn).into_iter()
+            .map(|mv| {
+                board.make_move(mv);
+                let count = board.perft_in_place(depth.saturating_sub(1));
+                board.unmake_move();
+                (mv, count)
+            })
+            .collect()
+    }
+    fn perft_in_place(&mut self, depth:u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.get_moves_2(self.player_turn);
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves.into_iter()
+            .map(|mv| {
+                self.make_move(mv);
+                let count = self.perft_in_place(depth-1);
+                self.unmake_move();
+                count
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Board,START_FEN};
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+    const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+    const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+    const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+    fn assert_perft(fen: &str, counts: &[u64]) {
+        let board = Board::from_fen(fen).unwrap();
+        for (depth,count) in counts.iter().enumerate() {
+            assert_eq!(board.perft(depth as u32 + 1), *count, "{} at depth {}", fen, depth + 1);
+        }
+    }
+
+    #[test]
+    fn start_position() {
+        assert_perft(START_FEN, &[20, 400, 8902, 197281]);
+    }
+    #[test]
+    fn kiwipete() {
+        assert_perft(KIWIPETE, &[48, 2039, 97862]);
+    }
+    #[test]
+    fn position_3() {
+        assert_perft(POSITION_3, &[14, 191, 2812, 43238]);
+    }
+    #[test]
+    fn position_4() {
+        assert_perft(POSITION_4, &[6, 264, 9467]);
+    }
+    #[test]
+    fn position_5() {
+        assert_perft(POSITION_5, &[44, 1486, 62379]);
+    }
+    #[test]
+    fn position_6() {
+        assert_perft(POSITION_6, &[46, 2079, 89890]);
+    }
+    #[test]
+    fn divide_sums_to_perft() {
+        let board = Board::from_fen(KIWIPETE).unwrap();
+        let divided = board.divide(2);
+        assert_eq!(divided.len(), 48);
+        assert_eq!(divided.iter().map(|(_,count)| count).sum::<u64>(), 2039);
+    }
+    #[test]
+    fn counts_past_automatic_draws() {
+        // Kxb1 leaves bare kings, a draw, but perft keeps counting the moves after it
+        assert_perft("k7/8/8/8/8/8/8/Kr6 w - - 0 1", &[2, 20, 57, 727]);
+        let divided = Board::from_fen("k7/8/8/8/8/8/8/Kr6 w - - 0 1").unwrap().divide(3);
+        assert_eq!(divided.iter().map(|(_,count)| count).sum::<u64>(), 57);
+    }
+
+    // the deeper counts take a while, run them with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn start_position_deep() {
+        assert_perft(START_FEN, &[20, 400, 8902, 197281, 4865609]);
+    }
+    #[test]
+    #[ignore]
+    fn kiwipete_deep() {
+        assert_perft(KIWIPETE, &[48, 2039, 97862, 4085603]);
+    }
+    #[test]
+    #[ignore]
+    fn position_3_deep() {
+        assert_perft(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+    }
+    #[test]
+    #[ignore]
+    fn position_4_deep() {
+        assert_perft(POSITION_4, &[6, 264, 9467, 422333]);
+    }
+    #[test]
+    #[ignore]
+    fn position_5_deep() {
+        assert_perft(POSITION_5, &[44, 1486, 62379, 2103487]);
+    }
+    #[test]
+    #[ignore]
+    fn position_6_deep() {
+        assert_perft(POSITION_6, &[46, 2079, 89890, 3894594]);
+    }
+}
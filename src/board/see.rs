@@ -0,0 +1,95 @@
+use super::{Board,move_pieces,tile_attackers};
+use super::super::moves::Move;
+use super::super::pieces::{ChessPiece,Kind};
+
+impl Board {
+    /// Static exchange evaluation: what `mv` wins on its target tile if both sides
+    /// keep recapturing with their least valuable piece and either may stop when it
+    /// pays to. Pieces are worth `value`, and pins are ignored.
+    pub fn see(&self, mv:Move, value:fn(Kind) -> i32) -> i32 {
+        let (from,to,promotion) = (mv.from_square(),mv.to_square(),mv.promotion());
+        let piece = self.get_piece(from).expect("~no piece to move");
+        let mut gains = Vec::<i32>::new();
+        let promotion_gain = promotion.map_or(0, |kind| value(kind) - value(Kind::PAWN));
+        gains.push(self.captured_piece(mv).map_or(0, |captured| value(captured.kind)) + promotion_gain);
+        let mut state = self.state;
+        move_pieces(&mut state,mv);
+        let mut on_tile = promotion.unwrap_or(piece.kind);
+        let mut side = piece.color.opposite();
+        loop {
+            // tile_attackers gives the pieces attacking a tile owned by the other color
+            let attackers = tile_attackers(&state,to,side.opposite());
+            let Some((attacker,square)) = attackers.into_iter()
+                .min_by_key(|(attacker,_)| if attacker.kind == Kind::KING { i32::MAX } else { value(attacker.kind) })
+            else { break };
+            let promoted = attacker.kind == Kind::PAWN && (to.rank() == 0 || to.rank() == 7);
+            let mut next = state;
+            next.set(square,None);
+            next.set(to,Some(if promoted { ChessPiece::new(side,Kind::QUEEN) } else { attacker }));
+            // the king can only take when nothing can take it back
+            if attacker.kind == Kind::KING && !tile_attackers(&next,to,side).is_empty() {
+                break;
+            }
+            let promotion_gain = if promoted { value(Kind::QUEEN) - value(Kind::PAWN) } else { 0 };
+            gains.push(value(on_tile) + promotion_gain - gains.last().expect("~gains start non empty"));
+            state = next;
+            on_tile = if promoted { Kind::QUEEN } else { attacker.kind };
+            side = side.opposite();
+        }
+        // each side only carries on with the exchange while it doesn't lose by doing so
+        while gains.len() > 1 {
+            let last = gains.pop().expect("~gains has two entries");
+            let previous = gains.last_mut().expect("~gains has one entry");
+            *previous = -(-*previous).max(last);
+        }
+        gains[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::super::notation;
+
+    fn value(kind: Kind) -> i32 {
+        match kind {
+            Kind::PAWN => 100,
+            Kind::KNIGHT | Kind::BISHOP => 300,
+            Kind::ROOK => 500,
+            Kind::QUEEN => 900,
+            Kind::KING => 0,
+        }
+    }
+    fn see(fen: &str, uci: &str) -> i32 {
+        let board = Board::from_fen(fen).unwrap();
+        let mv = notation::parse_uci(&board, uci).unwrap_or_else(|| panic!("{} is not legal", uci));
+        board.see(mv, value)
+    }
+
+    #[test]
+    fn reference_positions() {
+        // the rook wins a pawn nothing defends
+        assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+        // the knight is lost for a pawn once the queens and rooks have traded off
+        assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), -200);
+    }
+
+    #[test]
+    fn x_ray_recapture() {
+        // the rook behind the capturer takes back once the first one has gone
+        assert_eq!(see("4r1k1/8/8/4p3/8/8/4R3/4R1K1 w - - 0 1", "e2e5"), 100);
+    }
+
+    #[test]
+    fn king_cant_recapture_a_defended_tile() {
+        assert_eq!(see("8/8/4k3/3p4/8/1B6/8/3R2K1 w - - 0 1", "d1d5"), 100);
+        // without the bishop the king takes the rook
+        assert_eq!(see("8/8/4k3/3p4/8/8/8/3R2K1 w - - 0 1", "d1d5"), -400);
+    }
+
+    #[test]
+    fn quiet_promotion_onto_a_defended_tile() {
+        assert_eq!(see("r6k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"), -100);
+        assert_eq!(see("7k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7e8q"), 800);
+    }
+}
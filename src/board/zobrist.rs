@@ -0,0 +1,125 @@
+use super::{Board,CastlingRights,State,attacks,castling_rook,en_passant_victim};
+use super::super::moves::Move;
+use super::super::pieces::{ChessPiece,Color,Kind};
+use super::super::square::Square;
+
+// fixed pseudo-random keys, so hashes are the same from one run to the next
+const fn splitmix64(state: u64) -> (u64,u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+struct Keys {
+    pieces: [[u64; 64]; 12],
+    black_to_move: u64,
+    castling: [u64; 4],
+    en_passant_file: [u64; 8],
+}
+
+const KEYS: Keys = {
+    let mut keys = Keys {
+        pieces: [[0; 64]; 12],
+        black_to_move: 0,
+        castling: [0; 4],
+        en_passant_file: [0; 8],
+    };
+    let mut seed = 0x0C4E_55B0_A2D5_u64;
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            let (next,key) = splitmix64(seed);
+            seed = next;
+            keys.pieces[piece][square] = key;
+            square += 1;
+        }
+        piece += 1;
+    }
+    let (next,key) = splitmix64(seed);
+    seed = next;
+    keys.black_to_move = key;
+    let mut i = 0;
+    while i < 4 {
+        let (next,key) = splitmix64(seed);
+        seed = next;
+        keys.castling[i] = key;
+        i += 1;
+    }
+    let mut file = 0;
+    while file < 8 {
+        let (next,key) = splitmix64(seed);
+        seed = next;
+        keys.en_passant_file[file] = key;
+        file += 1;
+    }
+    keys
+};
+
+fn piece_key(piece: ChessPiece, square: Square) -> u64 {
+    KEYS.pieces[piece.index()][square.index()]
+}
+
+pub(super) fn castling_key(rights: &CastlingRights) -> u64 {
+    [rights.white_kingside, rights.white_queenside, rights.black_kingside, rights.black_queenside]
+        .iter()
+        .zip(KEYS.castling)
+        .filter(|(allowed,_)| **allowed)
+        .fold(0, |hash,(_,key)| hash ^ key)
+}
+
+pub(super) fn side_key(color: Color) -> u64 {
+    match color {
+        Color::WHITE => 0,
+        Color::BLACK => KEYS.black_to_move,
+    }
+}
+
+// the change in the piece part of the hash when `mv` is played on `state`,
+// following the same special cases as move_pieces
+pub(super) fn move_key(state: &State, mv: Move) -> u64 {
+    let (from,to) = (mv.from_square(),mv.to_square());
+    let piece = state.get(from).expect("~no piece to move");
+    let mut hash = piece_key(piece, from);
+    hash ^= piece_key(mv.promotion().map_or(piece, |kind| ChessPiece::new(piece.color, kind)), to);
+    if let Some(captured) = state.get(to) {
+        hash ^= piece_key(captured, to);
+    }
+    if mv.is_castle() {
+        let (rook_from,rook_to) = castling_rook(mv);
+        let rook = ChessPiece::new(piece.color, Kind::ROOK);
+        hash ^= piece_key(rook, rook_from) ^ piece_key(rook, rook_to);
+    }
+    else if mv.is_en_passant() {
+        hash ^= piece_key(ChessPiece::new(piece.color.opposite(), Kind::PAWN), en_passant_victim(mv));
+    }
+    hash
+}
+
+impl Board {
+    /// The Zobrist key of the position, equal for positions that are the same under
+    /// the repetition rules.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+    // the en passant file only counts when a pawn could actually take
+    pub(super) fn en_passant_key(&self) -> u64 {
+        // our pawns that could take are where an enemy pawn on the tile would attack
+        let pawns = self.state.pieces(self.player_turn,Kind::PAWN);
+        let capturable = self.en_passant
+            .filter(|square| attacks::pawn_attacks(self.player_turn.opposite(),*square) & pawns != 0);
+        match capturable {
+            Some(square) => KEYS.en_passant_file[square.file() as usize],
+            None => 0,
+        }
+    }
+    pub(super) fn compute_hash(&self) -> u64 {
+        let mut hash = side_key(self.player_turn) ^ castling_key(&self.castling_rights) ^ self.en_passant_key();
+        for (piece,square) in self.get_pieces() {
+            hash ^= piece_key(piece, square);
+        }
+        hash
+    }
+}
@@ -1,3 +0,0 @@
-pub mod pieces;
-pub mod board;
-pub mod ai;
@@ -0,0 +1,21 @@
+//! Chess rules, notation and a search engine, shared by the GUI and the engine binaries.
+//!
+//! - [`board`]: positions, legal move generation, game results and FEN
+//! - [`square`], [`moves`] and [`pieces`]: the basic types
+//! - [`notation`]: SAN, long algebraic and UCI move text
+//! - [`pgn`]: game records
+//! - [`ai`]: the search behind every front end
+
+pub mod pieces;
+pub mod square;
+pub mod moves;
+pub mod board;
+pub mod ai;
+pub mod pgn;
+pub mod notation;
+
+pub use ai::Ply;
+pub use board::{Board,GameResult};
+pub use moves::Move;
+pub use pieces::{ChessPiece,Color,Kind};
+pub use square::Square;
@@ -1,24 +1,42 @@
 use eframe::egui;
 use chess::ai;
-use chess::ai::Ply;
+use chess::ai::{SearchLimits,TranspositionTable};
+use chess::pgn::GameRecord;
+use std::io::Write;
 use std::collections::HashMap;
 use egui_extras::image::RetainedImage;
 use std::path::Path;
-mod chess;
-use chess::board::{Board,enemy_color};
+use std::sync::{Arc,Mutex};
+use std::sync::mpsc::{self,Receiver,TryRecvError};
+use std::time::Duration;
+use chess::board::{Board,PROMOTION_KINDS};
+use chess::moves::Move;
+use chess::square::Square;
 use chess::pieces::{Color,Kind,ChessPiece};
 
 fn main() {
-    let mut native_options = eframe::NativeOptions::default();
-    native_options.initial_window_size = Some(egui::Vec2{x: 1000.0, y: 1000.0});
-    native_options.resizable = false;
+    chess::board::init();
+    let native_options = eframe::NativeOptions {
+        initial_window_size: Some(egui::Vec2{x: 1000.0, y: 1000.0}),
+        resizable: false,
+        ..Default::default()
+    };
     eframe::run_native("Chess Game", native_options, Box::new(
             |cc| Box::new(MyEguiApp::new(cc))));
 }
 
+// every finished game is appended here so it can be replayed later
+const GAMES_FILE: &str = "games.pgn";
+const AI_THINKING_TIME: Duration = Duration::from_secs(2);
+
 struct MyEguiApp {
     tile_width: f32,
     board: Board,
+    pending_promotion: Option<Move>,
+    record: GameRecord,
+    // the computer's move arrives here once its search thread is done
+    ai_move: Option<Receiver<Option<Move>>>,
+    tt: Arc<Mutex<TranspositionTable>>,
     image_map: HashMap<(Kind, Color), RetainedImage>,
 }
 
@@ -26,9 +44,18 @@ impl Default for MyEguiApp {
     fn default() -> Self {
         let width = 1000.0;
         let num_tiles = 8.0;
+        let board = Board::default();
+        let mut record = GameRecord::from_position(&board);
+        record.set_tag("Event", "Casual game");
+        record.set_tag("White", "Player");
+        record.set_tag("Black", "Computer");
         Self {
             tile_width: width/num_tiles,
-            board: Board::default(),
+            board,
+            pending_promotion: None,
+            record,
+            ai_move: None,
+            tt: Arc::new(Mutex::new(TranspositionTable::new(TranspositionTable::DEFAULT_MB))),
             image_map: make_image_map(),
         }
     }
@@ -41,42 +68,128 @@ impl MyEguiApp {
     pub fn get_image(&self, color: Color, kind: Kind) -> &RetainedImage {
         self.image_map.get(&(kind, color)).unwrap()
     }
+    fn play_move(&mut self, mv: Move, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.board.perform_move_2(mv);
+        self.record.moves.push(mv);
+        frame.set_window_title(self.board.turn_str());
+        self.board.selected_tile = None;
+        if self.board.result.is_none() {
+            // now let ai have a turn, searching on its own thread so the window stays responsive
+            let (sender,receiver) = mpsc::channel();
+            let mut board = self.board.clone();
+            let ctx = ctx.clone();
+            let tt = Arc::clone(&self.tt);
+            std::thread::spawn(move || {
+                let mut tt = tt.lock().expect("~tt lock poisoned");
+                let mv = ai::make_move(&mut board, &SearchLimits::movetime(AI_THINKING_TIME), &mut tt);
+                let _ = sender.send(mv);
+                ctx.request_repaint();
+            });
+            self.ai_move = Some(receiver);
+        }
+        else {
+            self.save_game();
+        }
+    }
+    fn receive_ai_move(&mut self) {
+        let Some(receiver) = &self.ai_move else { return };
+        let mv = match receiver.try_recv() {
+            Ok(mv) => mv,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        self.ai_move = None;
+        if let Some(mv) = mv {
+            self.board.perform_move_2(mv);
+            self.record.moves.push(mv);
+        }
+        self.board.selected_tile = None;
+        if self.board.result.is_some() {
+            self.save_game();
+        }
+    }
+    fn save_game(&mut self) {
+        self.record.set_result(self.board.result);
+        let pgn = match self.record.to_pgn() {
+            Ok(pgn) => pgn,
+            Err(err) => {
+                eprintln!("couldn't write the game: {}", err);
+                return;
+            }
+        };
+        let saved = std::fs::OpenOptions::new().create(true).append(true).open(GAMES_FILE)
+            .and_then(|mut file| writeln!(file, "{}", pgn));
+        match saved {
+            Ok(()) => println!("game saved to {}", GAMES_FILE),
+            Err(err) => eprintln!("couldn't save the game: {}", err),
+        }
+    }
 }
 
 impl eframe::App for MyEguiApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        _frame.set_window_title(self.board.turn_str());
+        self.receive_ai_move();
+        let thinking = self.ai_move.is_some();
+        if thinking {
+            _frame.set_window_title("Computer is thinking...");
+        }
+        else {
+            _frame.set_window_title(self.board.turn_str());
+        }
+        let promoting = self.pending_promotion.is_some();
+        if let Some(mv) = self.pending_promotion {
+            egui::Window::new("Promote pawn")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        for kind in PROMOTION_KINDS {
+                            let image = self.get_image(self.board.player_turn(), kind);
+                            let button = egui::ImageButton::new(
+                                image.texture_id(ctx),
+                                egui::Vec2{x: self.tile_width, y: self.tile_width});
+                            if ui.add(button).clicked() {
+                                self.pending_promotion = None;
+                                let flags = Move::promotion_flags(kind) | mv.flags() & Move::CAPTURE;
+                                self.play_move(Move::new(mv.from_square(), mv.to_square(), flags), ctx, _frame);
+                            }
+                        }
+                    });
+                });
+        }
+        if let Some(reason) = self.board.claimable_draw().filter(|_| !thinking) {
+            egui::Window::new("Draw")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::RIGHT_TOP, egui::Vec2{x: -10.0, y: 10.0})
+                .show(ctx, |ui| {
+                    if ui.button(format!("Claim draw ({:?})", reason)).clicked() {
+                        self.board.claim_draw();
+                        self.save_game();
+                        _frame.set_window_title(self.board.turn_str());
+                    }
+                });
+        }
         egui::CentralPanel::default().show(ctx, |ui| {
-            if ctx.input().pointer.any_click() {
+            if ctx.input().pointer.any_click() && !promoting && !thinking {
                 match ctx.input().pointer.interact_pos() {
                     Some(egui::Pos2{x, y}) => {
                         let xpos = (x / self.tile_width).floor() as i32;
                         let ypos = (y / self.tile_width).floor() as i32;
-                        if let Some(_) = self.board.winner { return; }
-                        let available_moves = self.board.get_moves_2(self.board.player_turn);
-                        if available_moves.is_empty() {
-                            println!("no available moves");
-                            _frame.set_window_title(self.board.turn_str());
-                            return;
-                        }
-                        let mut made_move = false;
-                        for ply@Ply{fromx,fromy,tox,toy} in self.board.get_moves_2(self.board.player_turn) {
-                            if self.board.selected_tile.0 == fromx
-                                && self.board.selected_tile.1 == fromy
-                                && xpos == tox
-                                && ypos == toy {
-                                    self.board.perform_move_2(ply); // which function should process this?
-                                    _frame.set_window_title(self.board.turn_str());
-                                    self.board.selected_tile = (-1,-1);
-                                    made_move = true;
-                                    // now let ai have a turn
-                                    ai::make_move(&mut self.board);
-                                    _frame.set_window_title(self.board.turn_str());
-                                    self.board.selected_tile = (-1,-1);
-                            }
-                        }
-                        if !made_move {
-                            self.board.selected_tile = (xpos,ypos);
+                        if self.board.result.is_some() { return; }
+                        let clicked = Square::from_coords(xpos,ypos);
+                        let available_moves = self.board.get_moves_2(self.board.player_turn());
+                        let selected = available_moves.into_iter().find(|mv| {
+                            self.board.selected_tile == Some(mv.from_square()) && clicked == Some(mv.to_square())
+                        });
+                        match selected {
+                            Some(mv) if mv.is_promotion() => {
+                                // let the player pick the piece before the move is made
+                                self.pending_promotion = Some(mv);
+                            },
+                            Some(mv) => self.play_move(mv, ctx, _frame),
+                            None => self.board.selected_tile = clicked,
                         }
                     },
                     None => println!("no mouse pos"),
@@ -105,28 +218,26 @@ impl eframe::App for MyEguiApp {
                 for j in 0..8 {
                     let xpos = self.tile_width * j as f32 +0.0;
                     let ypos = self.tile_width * i as f32 +0.0;
-                    match self.board.get_piece(j,i) {
-                        Some(ChessPiece{color,kind}) => {
-                            let image = self.get_image(*color,*kind);
-                            ui.put(
-                                egui::Rect{min: egui::Pos2{x: xpos, y: ypos},
-                                           max: egui::Pos2{x: xpos + self.tile_width,
-                                                           y: ypos + self.tile_width }},
-                                egui::Image::new(
-                                    image.texture_id(ctx),
-                                    egui::Vec2{x:image.width() as f32,y:image.height() as f32})
-                            );
-                            if self.board.selected_tile == (j,i)
-                                && self.board.turn_piece_selected() {
-                                draw_tile_outline(xpos,ypos,self.tile_width,ui);
-                                //for (x,y) in self.board.get_moves(j,i,false) {
-                                    //draw_tile_outline(x as f32*self.tile_width,
-                                                      //y as f32*self.tile_width,
-                                                      //self.tile_width,ui);
-                                //}
-                            }
-                        },
-                        None => (),
+                    let square = Square::from_coords(j,i).expect("~tile off the board");
+                    if let Some(ChessPiece{color,kind}) = self.board.get_piece(square) {
+                        let image = self.get_image(color,kind);
+                        ui.put(
+                            egui::Rect{min: egui::Pos2{x: xpos, y: ypos},
+                                       max: egui::Pos2{x: xpos + self.tile_width,
+                                                       y: ypos + self.tile_width }},
+                            egui::Image::new(
+                                image.texture_id(ctx),
+                                egui::Vec2{x:image.width() as f32,y:image.height() as f32})
+                        );
+                        if self.board.selected_tile == Some(square)
+                            && self.board.turn_piece_selected() {
+                            draw_tile_outline(xpos,ypos,self.tile_width,ui);
+                            //for (x,y) in self.board.get_moves(j,i,false) {
+                                //draw_tile_outline(x as f32*self.tile_width,
+                                                  //y as f32*self.tile_width,
+                                                  //self.tile_width,ui);
+                            //}
+                        }
                     }
                 }
             }
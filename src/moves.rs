@@ -0,0 +1,158 @@
+use super::ai::Ply;
+use super::board::Board;
+use super::pieces::{ChessPiece,Kind};
+use super::square::Square;
+use std::fmt;
+
+/// A move packed into 16 bits: the from square in bits 0-5, the to square in
+/// bits 6-11 and the flags in bits 12-15.
+///
+/// The flags say what kind of move it is, so a `Move` can be told apart
+/// without the board it was made on. Promotions set 8 and keep the piece in
+/// the low two bits, captures set 4, and the two can be combined.
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash)]
+pub struct Move(u16);
+
+impl Move {
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PAWN_PUSH: u16 = 1;
+    pub const KING_CASTLE: u16 = 2;
+    pub const QUEEN_CASTLE: u16 = 3;
+    pub const CAPTURE: u16 = 4;
+    pub const EN_PASSANT: u16 = 5;
+    pub const PROMOTION: u16 = 8;
+
+    pub fn new(from: Square, to: Square, flags: u16) -> Self {
+        debug_assert!(flags < 16, "move flags only have four bits");
+        Self(from.index() as u16 | (to.index() as u16) << 6 | flags << 12)
+    }
+    /// The flags for promoting to `kind`, to be combined with `CAPTURE` when it takes a piece.
+    pub fn promotion_flags(kind: Kind) -> u16 {
+        Self::PROMOTION | match kind {
+            Kind::KNIGHT => 0,
+            Kind::BISHOP => 1,
+            Kind::ROOK => 2,
+            Kind::QUEEN => 3,
+            Kind::PAWN | Kind::KING => panic!("~can't promote to a {:?}", kind),
+        }
+    }
+    /// Encodes a legal `ply`, reading the flags off the board it's about to be played on.
+    pub fn from_ply(ply: Ply, board: &Board) -> Self {
+        let Ply{fromx,fromy,tox,toy,promotion} = ply;
+        let from = Square::from_coords(fromx,fromy).expect("~move starts off the board");
+        let to = Square::from_coords(tox,toy).expect("~move ends off the board");
+        let capture = if board.tile_occupied(to) { Self::CAPTURE } else { 0 };
+        let flags = match (board.get_piece(from), promotion) {
+            (_, Some(kind)) => Self::promotion_flags(kind) | capture,
+            (Some(ChessPiece{kind:Kind::KING,..}), _) if tox - fromx == 2 => Self::KING_CASTLE,
+            (Some(ChessPiece{kind:Kind::KING,..}), _) if tox - fromx == -2 => Self::QUEEN_CASTLE,
+            (Some(ChessPiece{kind:Kind::PAWN,..}), _) if board.en_passant() == Some(to) && fromx != tox => Self::EN_PASSANT,
+            (Some(ChessPiece{kind:Kind::PAWN,..}), _) if (toy - fromy).abs() == 2 => Self::DOUBLE_PAWN_PUSH,
+            _ => capture,
+        };
+        Self::new(from, to, flags)
+    }
+    /// The raw 16 bits, e.g. for storing in a table.
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+    pub fn from_square(self) -> Square {
+        Square::new((self.0 & 0x3f) as u8).expect("~six bits are always a square")
+    }
+    pub fn to_square(self) -> Square {
+        Square::new((self.0 >> 6 & 0x3f) as u8).expect("~six bits are always a square")
+    }
+    pub fn flags(self) -> u16 {
+        self.0 >> 12
+    }
+    pub fn is_capture(self) -> bool {
+        self.flags() & Self::CAPTURE != 0
+    }
+    pub fn is_en_passant(self) -> bool {
+        self.flags() == Self::EN_PASSANT
+    }
+    pub fn is_castle(self) -> bool {
+        matches!(self.flags(), Self::KING_CASTLE | Self::QUEEN_CASTLE)
+    }
+    pub fn is_promotion(self) -> bool {
+        self.flags() & Self::PROMOTION != 0
+    }
+    pub fn promotion(self) -> Option<Kind> {
+        if !self.is_promotion() {
+            return None;
+        }
+        Some(match self.flags() & 3 {
+            0 => Kind::KNIGHT,
+            1 => Kind::BISHOP,
+            2 => Kind::ROOK,
+            _ => Kind::QUEEN,
+        })
+    }
+}
+
+impl From<Move> for Ply {
+    fn from(mv: Move) -> Self {
+        let (fromx,fromy) = mv.from_square().coords();
+        let (tox,toy) = mv.to_square().coords();
+        Ply{fromx,fromy,tox,toy,promotion:mv.promotion()}
+    }
+}
+
+/// Coordinate notation, as in "e2e4" or "e7e8q".
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from_square(), self.to_square())?;
+        match self.promotion() {
+            Some(Kind::KNIGHT) => write!(f, "n"),
+            Some(Kind::BISHOP) => write!(f, "b"),
+            Some(Kind::ROOK) => write!(f, "r"),
+            Some(_) => write!(f, "q"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::notation;
+
+    #[test]
+    fn plies_round_trip() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+        for fen in fens {
+            let board = Board::from_fen(fen).unwrap();
+            for mv in board.legal_moves() {
+                let ply = Ply::from(mv);
+                assert_eq!(Move::from_ply(ply, &board), mv);
+                assert_eq!(Move::from_bits(mv.bits()), mv);
+                assert_eq!(notation::parse_uci(&board, &mv.to_string()), Some(mv));
+                assert_eq!(mv.is_capture(), board.captured_piece(mv).is_some(), "{}", mv);
+            }
+        }
+    }
+
+    #[test]
+    fn flags() {
+        let board = Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQK2R w KQkq f6 0 3").unwrap();
+        let encode = |text| Move::from_ply(Ply::from(notation::parse_uci(&board, text).unwrap()), &board);
+        assert_eq!(encode("e5f6").flags(), Move::EN_PASSANT);
+        assert!(encode("e5f6").is_capture());
+        assert_eq!(encode("d2d4").flags(), Move::DOUBLE_PAWN_PUSH);
+        assert_eq!(encode("e1g1").flags(), Move::KING_CASTLE);
+        assert!(encode("e1g1").is_castle());
+        assert_eq!(encode("b1c3").flags(), Move::QUIET);
+        let board = Board::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mv = Move::from_ply(Ply::from(notation::parse_uci(&board, "b7a8q").unwrap()), &board);
+        assert_eq!((mv.promotion(), mv.is_capture()), (Some(Kind::QUEEN), true));
+        assert_eq!(mv.flags(), Move::promotion_flags(Kind::QUEEN) | Move::CAPTURE);
+    }
+}
@@ -0,0 +1,290 @@
+use super::board::{Board,GameResult};
+use super::moves::Move;
+use super::pieces::Kind;
+use super::square::Square;
+
+pub fn piece_letter(kind: Kind) -> &'static str {
+    match kind {
+        Kind::PAWN => "",
+        Kind::KNIGHT => "N",
+        Kind::BISHOP => "B",
+        Kind::ROOK => "R",
+        Kind::QUEEN => "Q",
+        Kind::KING => "K",
+    }
+}
+
+/// Standard Algebraic Notation, e.g. "Nbd7", "exd6", "O-O-O" or "e8=Q+".
+pub fn san(board: &Board, mv: Move) -> String {
+    let (from,to) = (mv.from_square(),mv.to_square());
+    let piece = board.get_piece(from).expect("~no piece to move");
+    let mut text = String::new();
+    if mv.is_castle() {
+        text.push_str(if mv.flags() == Move::KING_CASTLE { "O-O" } else { "O-O-O" });
+    }
+    else {
+        let capture = mv.is_capture();
+        if piece.kind == Kind::PAWN {
+            if capture {
+                text.push_str(&from.to_string()[..1]);
+            }
+        }
+        else {
+            text.push_str(piece_letter(piece.kind));
+            // other pieces of the same kind that can reach the same tile
+            let rivals: Vec<Move> = board.get_moves_2(board.player_turn()).into_iter()
+                .filter(|other| other.to_square() == to && other.from_square() != from)
+                .filter(|other| board.get_piece(other.from_square()) == Some(piece))
+                .collect();
+            if !rivals.is_empty() {
+                let from_name = from.to_string();
+                if rivals.iter().all(|other| other.from_square().file() != from.file()) {
+                    text.push_str(&from_name[..1]);
+                }
+                else if rivals.iter().all(|other| other.from_square().rank() != from.rank()) {
+                    text.push_str(&from_name[1..]);
+                }
+                else {
+                    text.push_str(&from_name);
+                }
+            }
+        }
+        if capture {
+            text.push('x');
+        }
+        text.push_str(&to.to_string());
+        if let Some(kind) = mv.promotion() {
+            text.push('=');
+            text.push_str(piece_letter(kind));
+        }
+    }
+    text.push_str(check_suffix(board, mv));
+    text
+}
+
+/// Finds the legal move a SAN string describes. Check and annotation suffixes,
+/// capture signs and extra disambiguation are all optional, so long algebraic
+/// moves like "Ng1-f3" are read as well.
+pub fn parse_san(board: &Board, text: &str) -> Option<Move> {
+    let text = text.trim_end_matches(['+','#','!','?']);
+    let moves = board.get_moves_2(board.player_turn());
+    let castle = |flags| moves.iter().find(|mv| mv.flags() == flags).copied();
+    match text {
+        "O-O" | "0-0" => return castle(Move::KING_CASTLE),
+        "O-O-O" | "0-0-0" => return castle(Move::QUEEN_CASTLE),
+        _ => (),
+    }
+    let (text,promotion) = match text.split_once('=') {
+        Some((rest,piece)) => (rest, Some(parse_piece_letter(piece)?)),
+        // some sources leave out the '=', as in "e8Q"
+        None => match text.char_indices().last() {
+            Some((i,c)) if i > 0 && "NBRQ".contains(c) => (&text[..i], Some(parse_piece_letter(&text[i..])?)),
+            _ => (text, None),
+        },
+    };
+    let kind = match text.chars().next()? {
+        c if c.is_ascii_uppercase() => parse_piece_letter(&c.to_string())?,
+        _ => Kind::PAWN,
+    };
+    let text = if kind == Kind::PAWN { text } else { &text[1..] };
+    let text: String = text.chars().filter(|c| *c != 'x' && *c != '-').collect();
+    if text.len() < 2 || !text.is_ascii() {
+        return None;
+    }
+    let to: Square = text[text.len()-2..].parse().ok()?;
+    let mut file = None;
+    let mut rank = None;
+    for c in text[..text.len()-2].chars() {
+        match c {
+            'a'..='h' => file = Some(c as i32 - 'a' as i32),
+            '1'..='8' => rank = Some(c as i32 - '1' as i32),
+            _ => return None,
+        }
+    }
+    let mut candidates = moves.into_iter().filter(|mv| {
+        let from = mv.from_square();
+        mv.to_square() == to
+            && mv.promotion() == promotion
+            && file.is_none_or(|x| x == from.file())
+            && rank.is_none_or(|y| y == from.rank())
+            && board.get_piece(from).is_some_and(|piece| piece.kind == kind)
+    });
+    let mv = candidates.next()?;
+    // an ambiguous move doesn't name a single legal move
+    if candidates.next().is_some() {
+        return None;
+    }
+    Some(mv)
+}
+
+pub fn parse_piece_letter(letter: &str) -> Option<Kind> {
+    match letter {
+        "N" => Some(Kind::KNIGHT),
+        "B" => Some(Kind::BISHOP),
+        "R" => Some(Kind::ROOK),
+        "Q" => Some(Kind::QUEEN),
+        "K" => Some(Kind::KING),
+        _ => None,
+    }
+}
+
+/// Pure coordinate notation as used by UCI, e.g. "e2e4" or "e7e8q".
+pub fn uci(mv: Move) -> String {
+    mv.to_string()
+}
+
+/// Finds the legal move written in coordinate notation.
+pub fn parse_uci(board: &Board, text: &str) -> Option<Move> {
+    if text.len() < 4 || text.len() > 5 || !text.is_ascii() {
+        return None;
+    }
+    let from: Square = text[0..2].parse().ok()?;
+    let to: Square = text[2..4].parse().ok()?;
+    let promotion = match &text[4..] {
+        "" => None,
+        letter => Some(parse_piece_letter(&letter.to_ascii_uppercase()).filter(|kind| *kind != Kind::KING)?),
+    };
+    board.get_moves_2(board.player_turn()).into_iter()
+        .find(|mv| mv.from_square() == from && mv.to_square() == to && mv.promotion() == promotion)
+}
+
+/// Long algebraic notation, which always names the starting tile, e.g. "Ng1-f3" or "e5xd6".
+pub fn long_algebraic(board: &Board, mv: Move) -> String {
+    let piece = board.get_piece(mv.from_square()).expect("~no piece to move");
+    let mut text = String::new();
+    if mv.is_castle() {
+        text.push_str(if mv.flags() == Move::KING_CASTLE { "O-O" } else { "O-O-O" });
+    }
+    else {
+        text.push_str(piece_letter(piece.kind));
+        text.push_str(&mv.from_square().to_string());
+        text.push(if mv.is_capture() { 'x' } else { '-' });
+        text.push_str(&mv.to_square().to_string());
+        if let Some(kind) = mv.promotion() {
+            text.push('=');
+            text.push_str(piece_letter(kind));
+        }
+    }
+    text.push_str(check_suffix(board, mv));
+    text
+}
+
+fn check_suffix(board: &Board, mv: Move) -> &'static str {
+    let after = board.perform_move_copy(mv);
+    if matches!(after.result, Some(GameResult::WhiteWins) | Some(GameResult::BlackWins)) {
+        "#"
+    }
+    else if after.in_check(after.player_turn()) {
+        "+"
+    }
+    else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+    fn mv(board: &Board, text: &str) -> Move {
+        parse_uci(board, text).unwrap_or_else(|| panic!("{} is not legal", text))
+    }
+
+    const KNIGHTS: &str = "rnbqkb1r/ppp1pppp/5n2/3p4/3P4/5N2/PPP1PPPP/RNBQKB1R b KQkq - 1 3";
+    const EN_PASSANT: &str = "rnbqkbnr/ppp2ppp/4p3/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+    const CASTLING: &str = "r3k3/8/8/8/8/8/8/4K3 b q - 0 1";
+    const PROMOTION: &str = "8/4P3/8/8/k7/8/8/4K3 w - - 0 1";
+    const QUEENS: &str = "1k5K/8/8/3Q3Q/8/8/8/3Q4 w - - 0 1";
+
+    #[test]
+    fn writes_san() {
+        let san_of = |fen, text| { let board = board(fen); san(&board, mv(&board, text)) };
+        assert_eq!(san_of(KNIGHTS, "b8d7"), "Nbd7");
+        assert_eq!(san_of(KNIGHTS, "f6d7"), "Nfd7");
+        assert_eq!(san_of(KNIGHTS, "c7c5"), "c5");
+        assert_eq!(san_of(EN_PASSANT, "e5d6"), "exd6");
+        assert_eq!(san_of(CASTLING, "e8c8"), "O-O-O");
+        assert_eq!(san_of(PROMOTION, "e7e8q"), "e8=Q+");
+        assert_eq!(san_of(PROMOTION, "e7e8n"), "e8=N");
+        assert_eq!(san_of("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+    }
+
+    #[test]
+    fn disambiguates_by_file_rank_or_square() {
+        let san_of = |fen, text| { let board = board(fen); san(&board, mv(&board, text)) };
+        assert_eq!(san_of("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3"), "R1a3");
+        assert_eq!(san_of("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a5a3"), "R5a3");
+        assert_eq!(san_of(QUEENS, "h5h1"), "Qhh1");
+        assert_eq!(san_of(QUEENS, "d1h1"), "Q1h1");
+        assert_eq!(san_of(QUEENS, "d5h1"), "Qd5h1");
+        // a pinned knight isn't a rival, so no disambiguation is needed
+        assert_eq!(san_of("k3r3/8/8/8/8/8/N3N3/4K3 w - - 0 1", "a2c1"), "Nc1");
+    }
+
+    #[test]
+    fn reads_san() {
+        let board_ = board(KNIGHTS);
+        assert_eq!(parse_san(&board_, "Nbd7"), Some(mv(&board_, "b8d7")));
+        assert_eq!(parse_san(&board_, "N8d7"), Some(mv(&board_, "b8d7")));
+        assert_eq!(parse_san(&board_, "Nb8-d7!?"), Some(mv(&board_, "b8d7")));
+        assert_eq!(parse_san(&board_, "Nd7"), None);
+        assert_eq!(parse_san(&board_, "Nc5"), None);
+        assert_eq!(parse_san(&board_, "Zd7"), None);
+        let board_ = board(EN_PASSANT);
+        assert_eq!(parse_san(&board_, "exd6"), Some(mv(&board_, "e5d6")));
+        assert_eq!(parse_san(&board_, "ed6"), Some(mv(&board_, "e5d6")));
+        let board_ = board(CASTLING);
+        assert_eq!(parse_san(&board_, "O-O-O"), Some(mv(&board_, "e8c8")));
+        assert_eq!(parse_san(&board_, "0-0-0"), Some(mv(&board_, "e8c8")));
+        assert_eq!(parse_san(&board_, "O-O"), None);
+        let board_ = board(PROMOTION);
+        assert_eq!(parse_san(&board_, "e8=Q+"), Some(mv(&board_, "e7e8q")));
+        assert_eq!(parse_san(&board_, "e8N"), Some(mv(&board_, "e7e8n")));
+        assert_eq!(parse_san(&board_, "e8"), None);
+        assert_eq!(parse_san(&board_, "e8=K"), None);
+        let board_ = board(QUEENS);
+        assert_eq!(parse_san(&board_, "Qd5h1"), Some(mv(&board_, "d5h1")));
+        assert_eq!(parse_san(&board_, "Qdh1"), None);
+        assert_eq!(parse_san(&board_, "Q5h1"), None);
+    }
+
+    #[test]
+    fn san_round_trips() {
+        for fen in [KNIGHTS, EN_PASSANT, CASTLING, PROMOTION, QUEENS, "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"] {
+            let board = board(fen);
+            for mv in board.legal_moves() {
+                assert_eq!(parse_san(&board, &san(&board, mv)), Some(mv), "{} in {}", san(&board, mv), fen);
+                assert_eq!(parse_san(&board, &long_algebraic(&board, mv)), Some(mv));
+            }
+        }
+    }
+
+    #[test]
+    fn writes_long_algebraic() {
+        let long_of = |fen, text| { let board = board(fen); long_algebraic(&board, mv(&board, text)) };
+        assert_eq!(long_of(KNIGHTS, "b8d7"), "Nb8-d7");
+        assert_eq!(long_of(EN_PASSANT, "e5d6"), "e5xd6");
+        assert_eq!(long_of(CASTLING, "e8c8"), "O-O-O");
+        assert_eq!(long_of(PROMOTION, "e7e8q"), "e7-e8=Q+");
+        assert_eq!(long_of("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra1-a8#");
+    }
+
+    #[test]
+    fn reads_and_writes_uci() {
+        let board = board(PROMOTION);
+        let e7: Square = "e7".parse().unwrap();
+        let e8: Square = "e8".parse().unwrap();
+        let promotion = Move::new(e7, e8, Move::promotion_flags(Kind::QUEEN));
+        assert_eq!(parse_uci(&board, "e7e8q"), Some(promotion));
+        assert_eq!(uci(promotion), "e7e8q");
+        assert_eq!(uci(mv(&board, "e1d2")), "e1d2");
+        assert_eq!(parse_uci(&board, "e7e8"), None);
+        assert_eq!(parse_uci(&board, "e7e8k"), None);
+        assert_eq!(parse_uci(&board, "e1e3"), None);
+        assert_eq!(parse_uci(&board, "e1"), None);
+        assert_eq!(parse_uci(&board, "i1e2"), None);
+    }
+}
@@ -0,0 +1,392 @@
+use super::board::{Board,FenError,GameResult,START_FEN};
+use super::moves::Move;
+use super::notation;
+use super::pieces::Color;
+use std::fmt;
+
+// the Seven Tag Roster, always written first and in this order
+const ROSTER: [(&str,&str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+#[derive(Debug)]
+pub enum PgnError {
+    UnterminatedComment,
+    UnterminatedString,
+    UnterminatedVariation,
+    UnexpectedVariationEnd,
+    InvalidTag(String),
+    InvalidFen(FenError),
+    IllegalMove { ply: usize, san: String },
+    MissingResult,
+}
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::UnterminatedComment => write!(f, "comment is never closed"),
+            PgnError::UnterminatedString => write!(f, "tag value is never closed"),
+            PgnError::UnterminatedVariation => write!(f, "variation is never closed"),
+            PgnError::UnexpectedVariationEnd => write!(f, "')' without a matching '('"),
+            PgnError::InvalidTag(tag) => write!(f, "malformed tag pair near '{}'", tag),
+            PgnError::InvalidFen(err) => write!(f, "invalid FEN tag: {}", err),
+            PgnError::IllegalMove{ply,san} => write!(f, "move {} '{}' is not legal", ply, san),
+            PgnError::MissingResult => write!(f, "game text ends without a result"),
+        }
+    }
+}
+impl std::error::Error for PgnError {}
+impl From<FenError> for PgnError {
+    fn from(err: FenError) -> Self {
+        PgnError::InvalidFen(err)
+    }
+}
+
+/// A complete game: the PGN tag pairs and the moves from the starting position.
+#[derive(Clone,Debug)]
+pub struct GameRecord {
+    pub tags: Vec<(String,String)>,
+    pub moves: Vec<Move>,
+}
+impl Default for GameRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl GameRecord {
+    pub fn new() -> Self {
+        Self {
+            tags: ROSTER.iter().map(|(name,value)| (name.to_string(), value.to_string())).collect(),
+            moves: Vec::<Move>::new(),
+        }
+    }
+    /// A game that starts from a set up position instead of the usual one.
+    pub fn from_position(board: &Board) -> Self {
+        let mut record = Self::new();
+        let fen = board.to_fen();
+        if fen != START_FEN {
+            record.set_tag("SetUp", "1");
+            record.set_tag("FEN", &fen);
+        }
+        record
+    }
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n,_)| n == name).map(|(_,value)| value.as_str())
+    }
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n,_)| n == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+    pub fn set_result(&mut self, result: Option<GameResult>) {
+        self.set_tag("Result", result_str(result));
+    }
+    pub fn starting_board(&self) -> Result<Board, PgnError> {
+        match self.tag("FEN") {
+            Some(fen) => Ok(Board::from_fen(fen)?),
+            None => Ok(Board::default()),
+        }
+    }
+    /// Replays every move, giving the position at the end of the game.
+    pub fn final_board(&self) -> Result<Board, PgnError> {
+        let mut board = self.starting_board()?;
+        for (i,mv) in self.moves.iter().enumerate() {
+            if !board.get_moves_2(board.player_turn()).contains(mv) {
+                return Err(PgnError::IllegalMove{ply: i+1, san: notation::uci(*mv)});
+            }
+            board.perform_move_2(*mv);
+        }
+        Ok(board)
+    }
+    pub fn to_pgn(&self) -> Result<String, PgnError> {
+        let mut pgn = String::new();
+        let roster = ROSTER.iter().map(|(name,_)| *name);
+        let extra = self.tags.iter().map(|(name,_)| name.as_str()).filter(|name| !ROSTER.iter().any(|(n,_)| n == name));
+        for name in roster.chain(extra) {
+            let value = self.tag(name).unwrap_or("?");
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        pgn.push('\n');
+        let mut board = self.starting_board()?;
+        let mut tokens = Vec::<String>::new();
+        for (i,mv) in self.moves.iter().enumerate() {
+            let number = board.fullmove_number;
+            match board.player_turn() {
+                Color::WHITE => tokens.push(format!("{}.", number)),
+                Color::BLACK if i == 0 => tokens.push(format!("{}...", number)),
+                _ => (),
+            }
+            if !board.get_moves_2(board.player_turn()).contains(mv) {
+                return Err(PgnError::IllegalMove{ply: i+1, san: notation::uci(*mv)});
+            }
+            tokens.push(notation::san(&board, *mv));
+            board.perform_move_2(*mv);
+        }
+        tokens.push(self.tag("Result").unwrap_or("*").to_string());
+        // keep lines under 80 characters
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() + 1 > 79 {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        Ok(pgn)
+    }
+}
+
+pub fn result_str(result: Option<GameResult>) -> &'static str {
+    match result {
+        Some(GameResult::WhiteWins) => "1-0",
+        Some(GameResult::BlackWins) => "0-1",
+        Some(GameResult::Draw(_)) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+#[derive(Debug,PartialEq)]
+enum Token {
+    Tag(String,String),
+    Symbol(String),
+    VariationStart,
+    VariationEnd,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::<Token>::new();
+    let mut chars = text.chars().peekable();
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => { line_start = true; continue; },
+            // rest of line comments and the escape mechanism both run to the end of the line
+            ';' | '%' if c == ';' || line_start => {
+                chars.by_ref().find(|c| *c == '\n');
+                line_start = true;
+                continue;
+            },
+            '{' => {
+                if !chars.by_ref().any(|c| c == '}') {
+                    return Err(PgnError::UnterminatedComment);
+                }
+            },
+            '(' => tokens.push(Token::VariationStart),
+            ')' => tokens.push(Token::VariationEnd),
+            '[' => {
+                let inner: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                let name = inner.trim();
+                if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(PgnError::InvalidTag(inner));
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(PgnError::UnterminatedString),
+                    }
+                }
+                let rest: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                if !rest.trim().is_empty() {
+                    return Err(PgnError::InvalidTag(rest));
+                }
+                tokens.push(Token::Tag(name.to_string(), value));
+            },
+            c if c.is_whitespace() => (),
+            c => {
+                let mut symbol = c.to_string();
+                while let Some(next) = chars.peek() {
+                    if next.is_whitespace() || "{};()[".contains(*next) {
+                        break;
+                    }
+                    symbol.extend(chars.next());
+                }
+                tokens.push(Token::Symbol(symbol));
+            },
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+/// Reads every game in a PGN file, checking that each move is legal.
+/// Comments, NAGs and variations are skipped; only the main line is kept.
+pub fn parse_pgn(text: &str) -> Result<Vec<GameRecord>, PgnError> {
+    let mut games = Vec::<GameRecord>::new();
+    let mut tokens = tokenize(text)?.into_iter().peekable();
+    while tokens.peek().is_some() {
+        let mut record = GameRecord::new();
+        while let Some(Token::Tag(..)) = tokens.peek() {
+            if let Some(Token::Tag(name,value)) = tokens.next() {
+                record.set_tag(&name, &value);
+            }
+        }
+        let mut board = record.starting_board()?;
+        let mut variation_depth = 0;
+        let mut finished = false;
+        for token in tokens.by_ref() {
+            let symbol = match token {
+                Token::VariationStart => { variation_depth += 1; continue; },
+                Token::VariationEnd if variation_depth == 0 => return Err(PgnError::UnexpectedVariationEnd),
+                Token::VariationEnd => { variation_depth -= 1; continue; },
+                Token::Tag(name,_) => return Err(PgnError::InvalidTag(name)),
+                Token::Symbol(symbol) => symbol,
+            };
+            if variation_depth > 0 || symbol.starts_with('$') {
+                continue;
+            }
+            if ["1-0","0-1","1/2-1/2","*"].contains(&symbol.as_str()) {
+                record.set_tag("Result", &symbol);
+                finished = true;
+                break;
+            }
+            // move numbers may be glued to the move, as in "12.Nf3" or "12...Nf6",
+            // but only count as one when they end in a dot: "0-0" is castling
+            let after_digits = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+            let san_text = if after_digits.len() < symbol.len() && after_digits.starts_with('.') {
+                after_digits.trim_start_matches('.')
+            } else {
+                symbol.as_str()
+            };
+            // a standalone annotation like "!?" belongs to the move before it
+            if san_text.chars().all(|c| c == '!' || c == '?') {
+                continue;
+            }
+            let mv = notation::parse_san(&board, san_text)
+                .or_else(|| notation::parse_uci(&board, san_text));
+            match mv {
+                Some(mv) => {
+                    board.perform_move_2(mv);
+                    record.moves.push(mv);
+                },
+                None => return Err(PgnError::IllegalMove{ply: record.moves.len()+1, san: san_text.to_string()}),
+            }
+        }
+        if variation_depth > 0 {
+            return Err(PgnError::UnterminatedVariation);
+        }
+        if !finished {
+            return Err(PgnError::MissingResult);
+        }
+        games.push(record);
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(board: &Board, sans: &[&str]) -> Vec<Move> {
+        let mut board = board.clone();
+        sans.iter().map(|san| {
+            let mv = notation::parse_san(&board, san).unwrap_or_else(|| panic!("{} is not legal", san));
+            board.perform_move_2(mv);
+            mv
+        }).collect()
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut record = GameRecord::new();
+        record.set_tag("White", "Morphy, \"Paul\"");
+        record.set_tag("Annotator", "test");
+        record.moves = moves(&Board::default(), &[
+            "e4", "e5", "Nf3", "d6", "d4", "Bg4", "dxe5", "Bxf3", "Qxf3", "dxe5",
+            "Bc4", "Nf6", "Qb3", "Qe7", "Nc3", "c6", "Bg5", "b5", "Nxb5", "cxb5",
+            "Bxb5+", "Nbd7", "O-O-O", "Rd8", "Rxd7", "Rxd7", "Rd1", "Qe6", "Bxd7+", "Nxd7",
+            "Qb8+", "Nxb8", "Rd8#",
+        ]);
+        record.set_result(Some(GameResult::WhiteWins));
+        let pgn = record.to_pgn().unwrap();
+        assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n"));
+        assert!(pgn.contains("[White \"Morphy, \\\"Paul\\\"\"]\n"));
+        assert!(pgn.contains("[Annotator \"test\"]\n"));
+        assert!(pgn.contains("1. e4 e5 2. Nf3 d6"));
+        assert!(pgn.trim_end().ends_with("17. Rd8# 1-0"));
+        assert!(pgn.lines().all(|line| line.len() < 80));
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].moves, record.moves);
+        assert_eq!(games[0].tags, record.tags);
+        assert_eq!(games[0].final_board().unwrap().result, Some(GameResult::WhiteWins));
+    }
+
+    #[test]
+    fn reads_several_games() {
+        let text = "[Event \"One\"]\n\n1. e4 e5 1-0\n\n[Event \"Two\"]\n\n1. d4 d5 2. c4 0-1\n\n1. Nf3 *\n";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games.iter().map(|game| game.tag("Event").unwrap()).collect::<Vec<_>>(), ["One", "Two", "?"]);
+        assert_eq!(games.iter().map(|game| game.moves.len()).collect::<Vec<_>>(), [2, 3, 1]);
+        assert_eq!(games.iter().map(|game| game.tag("Result").unwrap()).collect::<Vec<_>>(), ["1-0", "0-1", "*"]);
+    }
+
+    #[test]
+    fn skips_comments_nags_and_variations() {
+        let text = "[Event \"?\"]\n\n\
+            1. e4 {best by test} e5 ; the rest of this line is ignored 2. d4\n\
+            2. Nf3 $1 (2. Bc4 Nf6 (2... Bc5 3. Qh5) 3. d3) Nc6 !? 3. Bb5 a6 $6 ?! 1/2-1/2\n";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games[0].moves, moves(&Board::default(), &["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]));
+    }
+
+    #[test]
+    fn reads_move_number_styles_and_castling() {
+        let text = "1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4. 0-0 Nf6 5.d3 5...O-O *";
+        let games = parse_pgn(text).unwrap();
+        assert_eq!(games[0].moves, moves(&Board::default(), &["e4", "e5", "Nf3", "Nc6", "Bc4", "Bc5", "O-O", "Nf6", "d3", "O-O"]));
+        let games = parse_pgn("1. e4 !? e5 ! 2. Nf3 ?? *").unwrap();
+        assert_eq!(games[0].moves.len(), 3);
+    }
+
+    #[test]
+    fn set_up_positions() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 40").unwrap();
+        let mut record = GameRecord::from_position(&board);
+        assert_eq!(record.tag("SetUp"), Some("1"));
+        assert_eq!(GameRecord::from_position(&Board::default()).tag("FEN"), None);
+        record.moves = moves(&board, &["Kd7", "e4", "Ke6"]);
+        let pgn = record.to_pgn().unwrap();
+        assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]"));
+        assert!(pgn.contains("40... Kd7 41. e4 Ke6 *"));
+        let games = parse_pgn(&pgn).unwrap();
+        assert_eq!(games[0].moves, record.moves);
+        assert_eq!(games[0].final_board().unwrap().to_fen(), "8/8/4k3/8/4P3/8/8/4K3 w - - 1 42");
+    }
+
+    #[test]
+    fn reports_errors() {
+        let error = |text: &str| parse_pgn(text).expect_err("PGN should be rejected");
+        assert!(matches!(error("1. e4 { never closed *"), PgnError::UnterminatedComment));
+        assert!(matches!(error("[Event \"never closed]"), PgnError::UnterminatedString));
+        assert!(matches!(error("1. e4 (1. d4 d5 *"), PgnError::UnterminatedVariation));
+        assert!(matches!(error("1. e4 ) e5 *"), PgnError::UnexpectedVariationEnd));
+        assert!(matches!(error("[Bad-Name \"x\"]\n*"), PgnError::InvalidTag(_)));
+        assert!(matches!(error("[Event \"x\" extra]\n*"), PgnError::InvalidTag(_)));
+        assert!(matches!(error("1. e4 [Event \"x\"] *"), PgnError::InvalidTag(_)));
+        assert!(matches!(error("[FEN \"8/8/8 w - - 0 1\"]\n*"), PgnError::InvalidFen(FenError::WrongRankCount(3))));
+        match error("1. e4 e5 2. Ke3 *") {
+            PgnError::IllegalMove{ply,san} => assert_eq!((ply,san.as_str()), (3,"Ke3")),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(matches!(error("1. e4 e5"), PgnError::MissingResult));
+        let mut record = GameRecord::new();
+        record.moves.push(Move::new("e2".parse().unwrap(), "e5".parse().unwrap(), Move::QUIET));
+        assert!(matches!(record.to_pgn(), Err(PgnError::IllegalMove{ply: 1, ..})));
+        assert!(matches!(record.final_board(), Err(PgnError::IllegalMove{ply: 1, ..})));
+    }
+}
@@ -0,0 +1,62 @@
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq,Eq,Hash,Copy,Clone,Debug)]
+pub enum Color {
+    WHITE,
+    BLACK,
+}
+impl Color {
+    pub fn opposite(self) -> Self {
+        match self {
+            Color::WHITE => Color::BLACK,
+            Color::BLACK => Color::WHITE,
+        }
+    }
+    /// 0 for white and 1 for black, for indexing tables.
+    pub fn index(self) -> usize {
+        match self {
+            Color::WHITE => 0,
+            Color::BLACK => 1,
+        }
+    }
+}
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq,Eq,Hash,Copy,Clone,Debug)]
+pub enum Kind {
+    PAWN,
+    KNIGHT,
+    ROOK,
+    BISHOP,
+    QUEEN,
+    KING
+}
+impl Kind {
+    /// 0 for pawns up to 5 for kings, in order of value, for indexing tables.
+    pub fn index(self) -> usize {
+        match self {
+            Kind::PAWN => 0,
+            Kind::KNIGHT => 1,
+            Kind::BISHOP => 2,
+            Kind::ROOK => 3,
+            Kind::QUEEN => 4,
+            Kind::KING => 5,
+        }
+    }
+}
+#[derive(PartialEq,Eq,Hash,Clone,Copy,Debug)]
+pub struct ChessPiece {
+    pub kind: Kind,
+    pub color: Color,
+}
+impl ChessPiece {
+    pub fn new(color: Color, kind: Kind) -> Self {
+        Self{color, kind}
+    }
+    /// The same kind of piece for the other side.
+    pub fn opposite(self) -> Self {
+        Self{color: self.color.opposite(), ..self}
+    }
+    /// 0..12, white's pieces first.
+    pub fn index(self) -> usize {
+        self.color.index() * 6 + self.kind.index()
+    }
+}
@@ -0,0 +1,94 @@
+use super::board::Bitboard;
+use std::fmt;
+use std::str::FromStr;
+
+/// A tile of the board as one index: a1 is 0, b1 is 1 and h8 is 63, the same
+/// order as the bits of a [`Bitboard`].
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash,PartialOrd,Ord)]
+pub struct Square(u8);
+
+impl Square {
+    pub fn new(index: u8) -> Option<Self> {
+        (index < 64).then_some(Self(index))
+    }
+    /// The square on `file` (0 for a) and `rank` (0 for the first rank), if it's on the board.
+    pub fn from_coords(file: i32, rank: i32) -> Option<Self> {
+        ((0..8).contains(&file) && (0..8).contains(&rank)).then(|| Self((rank * 8 + file) as u8))
+    }
+    /// Every square, a1 first.
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Self)
+    }
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+    pub fn file(self) -> i32 {
+        (self.0 % 8) as i32
+    }
+    pub fn rank(self) -> i32 {
+        (self.0 / 8) as i32
+    }
+    /// The (x,y) the older code addresses tiles by.
+    pub fn coords(self) -> (i32,i32) {
+        (self.file(), self.rank())
+    }
+    pub fn bitboard(self) -> Bitboard {
+        1 << self.0
+    }
+    /// The square `dx` files and `dy` ranks away, unless that's off the board.
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Self> {
+        Self::from_coords(self.file() + dx, self.rank() + dy)
+    }
+}
+
+impl From<Square> for (i32,i32) {
+    fn from(square: Square) -> Self {
+        square.coords()
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file() as u8) as char, self.rank() + 1)
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ParseSquareError(pub String);
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' is not a square", self.0)
+    }
+}
+impl std::error::Error for ParseSquareError {}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+    /// Reads algebraic names like "e4".
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Ok(Self((rank - b'1') * 8 + (file - b'a'))),
+            _ => Err(ParseSquareError(name.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for square in Square::all() {
+            assert_eq!(square.to_string().parse(), Ok(square));
+            assert_eq!(Square::from_coords(square.file(), square.rank()), Some(square));
+        }
+        let e4: Square = "e4".parse().unwrap();
+        assert_eq!((e4.index(), e4.coords()), (28, (4,3)));
+        assert!("i1".parse::<Square>().is_err());
+        assert!("a9".parse::<Square>().is_err());
+        assert!("e".parse::<Square>().is_err());
+        assert_eq!(Square::from_coords(8, 0), None);
+        assert_eq!(e4.offset(-4, 4).map(|square| square.to_string()), Some("a8".to_string()));
+    }
+}